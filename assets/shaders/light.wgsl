@@ -15,6 +15,6 @@ fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
 
     let voronoi = voro_noise(vec2(globals.time, globals.time), 1.0, 1.0) * opacity;
 
-    return vec4(material_color.xyz, voronoi);
+    return vec4(material_color.xyz, voronoi * material_color.w);
 }
 
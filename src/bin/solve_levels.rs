@@ -0,0 +1,621 @@
+//! Checks that every level of an LDtk project can be solved, using the headless
+//! [`LightSolverWorld`]. For each level, it searches the shots Lyra can make from the places she
+//! can reach from `Start`, and reports whether she can reach the `EndMarker`, the fewest shots it
+//! takes, and every distinct order of light colors that solves the level.
+//!
+//! ```sh
+//! cargo run --release --bin solve_levels -- assets/levels/lightborne.ldtk
+//! ```
+//!
+//! Lyra's movement is approximated on the level's grid: she walks, falls, and jumps up to
+//! [`JUMP_HEIGHT`] cells high and [`JUMP_RANGE`] cells far. Beams only change the level through
+//! the sensors they charge, so shots whose beams hit neither a sensor nor a crystal that could
+//! open up are skipped, and shots that take the same path are only tried once.
+//!
+//! Levels with things the solver doesn't model (e.g. prisms, buttons, or beams that block
+//! each other) are reported as unsupported instead, with what is missing.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    f32::consts::TAU,
+    process::ExitCode,
+};
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{
+    ldtk::{LdtkJson, Level, Type},
+    prelude::*,
+};
+use lightborne::{
+    game::{
+        defs::{
+            crystal::{Crystal, CrystalColor},
+            sensor::SensorMode,
+        },
+        light::{
+            solver::{LightSolverWorld, SolverObject},
+            LightColor, LightHits,
+        },
+        lyra::{
+            beam::{snap_ray, NUM_INCREMENTS},
+            indicator::LYRA_SHARD_HOLD_Y,
+        },
+    },
+    ldtk::LevelExt,
+};
+
+const TERRAIN_LAYER_IDENT: &str = "Terrain";
+const ENTITY_LAYER_IDENT: &str = "Entities";
+const END_MARKER_IDENT: &str = "EndMarker";
+/// Entities that change how beams travel or how the level reacts to them, which the solver doesn't
+/// model
+const UNSUPPORTED_ENTITY_IDENTS: [&str; 9] = [
+    "Prism",
+    "Portal",
+    "LightEmitter",
+    "HazardLaser",
+    "MovingPlatform",
+    "Vine",
+    "Button",
+    "LogicGate",
+    "CrystalLink",
+];
+
+/// Lyra's size, in cells
+const LYRA_CELLS: IVec2 = IVec2::new(2, 2);
+/// How many cells high Lyra can jump
+const JUMP_HEIGHT: i32 = 2;
+/// How many cells Lyra can move sideways in the air
+const JUMP_RANGE: i32 = 5;
+/// How many times sensors toggling crystals are allowed to change the beams before the state of the
+/// level is taken as is
+const MAX_SETTLE_ITERATIONS: usize = 8;
+
+fn main() -> ExitCode {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("usage: solve_levels <path to .ldtk project>");
+        return ExitCode::FAILURE;
+    };
+    let project: LdtkJson = match std::fs::read_to_string(&path)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| serde_json::from_str(&contents).map_err(|err| err.to_string()))
+    {
+        Ok(project) => project,
+        Err(err) => {
+            eprintln!("could not load {path}: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut levels: Vec<&Level> = project
+        .levels
+        .iter()
+        .filter(|level| level.get_bool_field("Selectable").is_ok_and(|x| *x))
+        .collect();
+    levels.sort_by(|a, b| a.level_id().cmp(b.level_id()));
+
+    let mut all_solvable = true;
+    let mut reports = vec![];
+    for level in levels {
+        let report = solve_level(level);
+        match &report {
+            Ok(report) => {
+                println!("{} ({}):", level.level_id(), level.identifier);
+                match report.min_shots {
+                    Some(shots) => println!("    solvable in {shots} shot(s)"),
+                    None if !report.unsupported.is_empty() => {
+                        println!("    unsupported: {}", report.unsupported.join(", "));
+                    }
+                    None => {
+                        println!("    UNSOLVABLE");
+                        all_solvable = false;
+                    }
+                }
+                for order in report.orders.iter() {
+                    println!("    {order:?}");
+                }
+            }
+            Err(err) => {
+                println!("{} ({}): {err}", level.level_id(), level.identifier);
+                all_solvable = false;
+            }
+        }
+        reports.push(report);
+    }
+
+    let counts: Vec<String> = reports
+        .iter()
+        .map(|report| match report {
+            Ok(LevelReport {
+                min_shots: Some(shots),
+                ..
+            }) => shots.to_string(),
+            _ => "_".to_string(),
+        })
+        .collect();
+    println!();
+    println!("DESIRED_COUNTS: [{}]", counts.join(", "));
+    println!("official_sols:");
+    for report in reports.iter() {
+        let orders = report
+            .as_ref()
+            .map(|report| &report.orders[..])
+            .unwrap_or(&[]);
+        println!("    {orders:?},");
+    }
+
+    if all_solvable {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+#[derive(Debug, Default)]
+struct LevelReport {
+    min_shots: Option<usize>,
+    /// The distinct orders of light colors that solve the level with no unneeded shots
+    orders: Vec<Vec<LightColor>>,
+    /// What the level has that the solver doesn't model, if it wasn't searched
+    unsupported: Vec<String>,
+}
+
+/// Identifies shots that play out the same way, however the crystals are toggled.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ShotKey {
+    color: LightColor,
+    /// The index of the direction of the shot
+    dir: usize,
+    /// Where the shot starts, if the beam crosses a crystal that could block it before its first
+    /// hit
+    start: Option<IVec2>,
+    /// Every point the beam hits, rounded to the nearest pixel
+    hits: Vec<IVec2>,
+}
+
+/// A beam Lyra shot, which stays in the level.
+#[derive(Debug, Clone, Copy)]
+struct Shot {
+    color: LightColor,
+    pos: Vec2,
+    dir: Dir2,
+}
+
+fn crystal_bit(color: CrystalColor) -> u8 {
+    match color {
+        CrystalColor::Pink => 1,
+        CrystalColor::Red => 2,
+        CrystalColor::White => 4,
+        CrystalColor::Blue => 8,
+    }
+}
+
+/// The terrain of a level, used to find where Lyra can go. Cells are indexed with y going down,
+/// like in LDtk.
+struct LevelGrid {
+    width: i32,
+    height: i32,
+    grid_size: f32,
+    origin: Vec2,
+    cells: Vec<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CellKind {
+    Empty,
+    Solid,
+    Platform,
+    Deadly,
+}
+
+impl LevelGrid {
+    fn from_level(level: &Level) -> Option<Self> {
+        let layer = level.layer_instances.as_ref()?.iter().find(|layer| {
+            layer.identifier == TERRAIN_LAYER_IDENT && layer.layer_instance_type == Type::IntGrid
+        })?;
+        Some(LevelGrid {
+            width: layer.c_wid,
+            height: layer.c_hei,
+            grid_size: layer.grid_size as f32,
+            origin: Vec2::new(level.world_x as f32, -level.world_y as f32),
+            cells: layer.int_grid_csv.clone(),
+        })
+    }
+
+    /// The kind of the cell, given the crystal colors that were toggled.
+    fn kind(&self, cell: IVec2, toggled: u8) -> CellKind {
+        // Lyra can't leave the level
+        if cell.x < 0 || cell.y < 0 || cell.x >= self.width || cell.y >= self.height {
+            return CellKind::Solid;
+        }
+        match self.cells[(cell.y * self.width + cell.x) as usize] {
+            0 | 26 => CellKind::Empty,
+            2 => CellKind::Deadly,
+            value @ 3..=10 => {
+                let crystal = Crystal::from(IntGridCell { value });
+                if crystal.active != (toggled & crystal_bit(crystal.color) != 0) {
+                    CellKind::Solid
+                } else {
+                    CellKind::Empty
+                }
+            }
+            15 => CellKind::Platform,
+            _ => CellKind::Solid,
+        }
+    }
+
+    /// The cells Lyra covers when the bottom left of her is in `cell`.
+    fn footprint(cell: IVec2) -> impl Iterator<Item = IVec2> {
+        (0..LYRA_CELLS.x)
+            .flat_map(move |dx| (0..LYRA_CELLS.y).map(move |dy| cell + IVec2::new(dx, -dy)))
+    }
+
+    fn fits(&self, cell: IVec2, toggled: u8) -> bool {
+        Self::footprint(cell).all(|cell| {
+            matches!(
+                self.kind(cell, toggled),
+                CellKind::Empty | CellKind::Platform
+            )
+        })
+    }
+
+    fn standable(&self, cell: IVec2, toggled: u8) -> bool {
+        self.fits(cell, toggled)
+            && (0..LYRA_CELLS.x).any(|dx| {
+                matches!(
+                    self.kind(cell + IVec2::new(dx, 1), toggled),
+                    CellKind::Solid | CellKind::Platform
+                )
+            })
+    }
+
+    /// Where Lyra lands when she falls from `cell`, or [`None`] if she doesn't fit there.
+    fn fall(&self, mut cell: IVec2, toggled: u8) -> Option<IVec2> {
+        if !self.fits(cell, toggled) {
+            return None;
+        }
+        while !self.standable(cell, toggled) {
+            cell.y += 1;
+            if !self.fits(cell, toggled) {
+                return None;
+            }
+        }
+        Some(cell)
+    }
+
+    /// Finds where Lyra can stand and every place she can pass through, starting from `start`.
+    fn reachable(&self, start: IVec2, toggled: u8) -> (Vec<IVec2>, HashSet<IVec2>) {
+        let mut standing = vec![];
+        let mut visited = HashSet::new();
+        // the start flag isn't always aligned to the grid
+        let start = [0, -1, 1]
+            .into_iter()
+            .flat_map(|dy| [0, -1, 1].map(|dx| start + IVec2::new(dx, dy)))
+            .find_map(|cell| self.fall(cell, toggled));
+        let Some(start) = start else {
+            return (standing, visited);
+        };
+
+        let mut seen = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(cell) = queue.pop_front() {
+            standing.push(cell);
+            visited.insert(cell);
+            // rise straight up, then move sideways, then fall
+            for h in 0..=JUMP_HEIGHT {
+                let top = cell - IVec2::new(0, h);
+                if !self.fits(top, toggled) {
+                    break;
+                }
+                visited.insert(top);
+                for dir in [-1, 0, 1] {
+                    for d in 1..=JUMP_RANGE {
+                        let air = top + IVec2::new(dir * d, 0);
+                        if !self.fits(air, toggled) {
+                            break;
+                        }
+                        visited.insert(air);
+                        if let Some(land) = self.fall(air, toggled) {
+                            visited.extend((air.y..=land.y).map(|y| IVec2::new(air.x, y)));
+                            if seen.insert(land) {
+                                queue.push_back(land);
+                            }
+                        }
+                        if dir == 0 {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        (standing, visited)
+    }
+
+    /// The cell Lyra is in when her center is at `pos`.
+    fn cell_at(&self, pos: Vec2) -> IVec2 {
+        let local = Vec2::new(pos.x - self.origin.x, self.origin.y - pos.y) / self.grid_size;
+        IVec2::new(
+            local.x.round() as i32 - LYRA_CELLS.x / 2,
+            local.y.round() as i32 + LYRA_CELLS.y / 2 - 1,
+        )
+    }
+
+    /// Where Lyra shoots from when she stands in `cell`.
+    fn shoot_pos(&self, cell: IVec2) -> Vec2 {
+        let center = Vec2::new(
+            (cell.x + LYRA_CELLS.x / 2) as f32,
+            -((cell.y + 1 - LYRA_CELLS.y / 2) as f32),
+        );
+        self.origin + center * self.grid_size + Vec2::new(0., LYRA_SHARD_HOLD_Y)
+    }
+
+    /// The cells covered by the level's `EndMarker`, if it has one.
+    fn end_marker_cells(&self, level: &Level) -> Option<HashSet<IVec2>> {
+        let entity = level
+            .layer_instances
+            .as_ref()?
+            .iter()
+            .filter(|layer| layer.identifier == ENTITY_LAYER_IDENT)
+            .flat_map(|layer| layer.entity_instances.iter())
+            .find(|entity| entity.identifier == END_MARKER_IDENT)?;
+        let size = Vec2::new(entity.width as f32, entity.height as f32);
+        let min = (entity.px.as_vec2() - entity.pivot * size) / self.grid_size;
+        let max = min + size / self.grid_size;
+        let min = min.floor().as_ivec2();
+        let max = max.ceil().as_ivec2();
+        Some(
+            (min.x..max.x)
+                .flat_map(|x| (min.y..max.y).map(move |y| IVec2::new(x, y)))
+                .collect(),
+        )
+    }
+}
+
+/// Plays the shots one at a time, each until the crystals toggled by the sensors they hit stop
+/// changing, returning the world with the toggled crystals. Latches and one shots stay on once
+/// they are charged, even after the beam that charged them is blocked.
+fn settle(base: &LightSolverWorld, shots: &[Shot]) -> (LightSolverWorld, u8) {
+    let mut toggled = 0;
+    let mut world = base.clone();
+    // the latches and one shots that have been charged
+    let mut held = vec![false; base.sensors.len()];
+    for fired in 0..=shots.len() {
+        for _ in 0..MAX_SETTLE_ITERATIONS {
+            let mut hit_by = vec![LightHits::default(); world.sensors.len()];
+            for shot in &shots[..fired] {
+                let beam = world.solve(shot.pos, shot.dir, shot.color);
+                for sensor in beam.sensors_hit {
+                    hit_by[sensor][shot.color] += 1;
+                }
+            }
+
+            let mut next = 0;
+            for (i, sensor) in world.sensors.iter().enumerate() {
+                let lit = sensor.requirement.is_met(&hit_by[i]);
+                let active = match sensor.mode {
+                    SensorMode::Hold => lit,
+                    // inverted sensors are active while their requirement isn't met
+                    SensorMode::Inverted => !lit,
+                    // latches stay on until a black beam drains them
+                    SensorMode::Latch => lit || (held[i] && hit_by[i][LightColor::Black] == 0),
+                    SensorMode::OneShot => lit || held[i],
+                };
+                if matches!(sensor.mode, SensorMode::Latch | SensorMode::OneShot) {
+                    held[i] = active;
+                }
+                if active && sensor.event_id.is_none() {
+                    next ^= crystal_bit(sensor.toggle_color);
+                }
+            }
+            if next == toggled {
+                break;
+            }
+            toggled = next;
+            world = base.clone();
+            for color in [
+                CrystalColor::Pink,
+                CrystalColor::Red,
+                CrystalColor::White,
+                CrystalColor::Blue,
+            ] {
+                if toggled & crystal_bit(color) != 0 {
+                    world.toggle_crystals(color);
+                }
+            }
+        }
+    }
+    (world, toggled)
+}
+
+/// What the level has that the solver doesn't model.
+fn unsupported_features(
+    level: &Level,
+    world: &LightSolverWorld,
+    allowed: &[LightColor],
+) -> Vec<String> {
+    let mut unsupported = vec![];
+    let layers = level.layer_instances.as_deref().unwrap_or_default();
+
+    let tiles: HashSet<i32> = layers
+        .iter()
+        .filter(|layer| layer.identifier == TERRAIN_LAYER_IDENT)
+        .flat_map(|layer| layer.int_grid_csv.iter().copied())
+        .collect();
+    for (values, name) in [
+        (17..=24, "glass"),
+        (25..=26, "lenses or water"),
+        (35..=36, "phase blocks"),
+        (37..=41, "cracked walls"),
+    ] {
+        if values.into_iter().any(|value| tiles.contains(&value)) {
+            unsupported.push(name.to_string());
+        }
+    }
+
+    let mut entities: Vec<&str> = layers
+        .iter()
+        .filter(|layer| layer.identifier == ENTITY_LAYER_IDENT)
+        .flat_map(|layer| layer.entity_instances.iter())
+        .map(|entity| entity.identifier.as_str())
+        .filter(|identifier| UNSUPPORTED_ENTITY_IDENTS.contains(identifier))
+        .collect();
+    entities.sort();
+    entities.dedup();
+    unsupported.extend(entities.into_iter().map(str::to_string));
+
+    if world.sensors.iter().any(|sensor| sensor.event_id.is_some()) {
+        unsupported.push("sensors with an event_id".to_string());
+    }
+    if level.light_bridges().values().any(|bridge| *bridge) {
+        unsupported.push("LightBridges".to_string());
+    }
+    if level.beam_range().is_some() {
+        unsupported.push("BeamRange".to_string());
+    }
+    // the segments of some beams have colliders that other beams hit
+    let blocking = allowed.iter().any(|color| {
+        let layer = world.palette[*color].collision_layers().memberships;
+        allowed.iter().any(|other| {
+            other != color
+                && world.palette[*other]
+                    .collision_layers()
+                    .filters
+                    .has_all(layer)
+        })
+    });
+    if blocking {
+        unsupported.push("beams that block each other".to_string());
+    }
+    unsupported
+}
+
+/// If the segment from `a` to `b` crosses a crystal that is inactive, which could block it once
+/// toggled.
+fn crosses_inactive_crystal(world: &LightSolverWorld, a: Vec2, b: Vec2) -> bool {
+    let Ok(dir) = Dir2::new(b - a) else {
+        return false;
+    };
+    world.colliders.iter().any(|collider| {
+        matches!(collider.object, SolverObject::Crystal { active: false, .. })
+            && collider
+                .collider
+                .cast_ray(
+                    collider.position,
+                    collider.rotation,
+                    a,
+                    *dir,
+                    a.distance(b),
+                    true,
+                )
+                .is_some()
+    })
+}
+
+/// If every color in `short` appears in `long` in the same order, possibly with other colors in
+/// between.
+fn is_subsequence(short: &[LightColor], long: &[LightColor]) -> bool {
+    let mut long = long.iter();
+    short.iter().all(|color| long.any(|other| other == color))
+}
+
+fn solve_level(level: &Level) -> Result<LevelReport, String> {
+    let grid = LevelGrid::from_level(level).ok_or("no terrain layer")?;
+    let end = grid.end_marker_cells(level).ok_or("no EndMarker")?;
+    let start = level.start_flag_pos().ok_or("no Start")?;
+    let start = grid.cell_at(start);
+    let base = LightSolverWorld::from_level(level);
+
+    let allowed: Vec<LightColor> = level
+        .get_maybe_enums_field("AllowedColors")
+        .map(|colors| colors.iter().flatten().map(LightColor::from).collect())
+        .unwrap_or_default();
+    let charges = level.light_charges();
+    let max_shots: usize = allowed.iter().map(|color| charges[*color]).sum();
+    let dirs: Vec<Dir2> = (0..NUM_INCREMENTS)
+        .map(|i| Vec2::from_angle(i as f32 * TAU / NUM_INCREMENTS as f32))
+        .filter_map(|dir| Dir2::new(snap_ray(dir)).ok())
+        .collect();
+
+    let mut report = LevelReport {
+        unsupported: unsupported_features(level, &base, &allowed),
+        ..default()
+    };
+    if !report.unsupported.is_empty() {
+        return Ok(report);
+    }
+
+    // sequences of shots are searched in order of length, and are identified by the path of each
+    // shot
+    let mut seen: HashSet<Vec<ShotKey>> = HashSet::new();
+    let mut queue: VecDeque<(Vec<Shot>, Vec<ShotKey>)> = VecDeque::from([(vec![], vec![])]);
+    while let Some((shots, key)) = queue.pop_front() {
+        let (world, toggled) = settle(&base, &shots);
+        let (standing, visited) = grid.reachable(start, toggled);
+        let reached = visited
+            .iter()
+            .any(|cell| LevelGrid::footprint(*cell).any(|cell| end.contains(&cell)));
+        if reached {
+            report.min_shots = Some(report.min_shots.unwrap_or(shots.len()));
+            let order: Vec<LightColor> = shots.iter().map(|shot| shot.color).collect();
+            // shorter orders are found first, so an order that contains one of them has shots
+            // that weren't needed
+            if !report
+                .orders
+                .iter()
+                .any(|solved| is_subsequence(solved, &order))
+            {
+                report.orders.push(order);
+            }
+            continue;
+        }
+        if shots.len() >= max_shots {
+            continue;
+        }
+
+        let mut used: HashMap<LightColor, usize> = HashMap::new();
+        for shot in shots.iter() {
+            *used.entry(shot.color).or_default() += 1;
+        }
+        for color in allowed.iter().copied() {
+            if used.get(&color).copied().unwrap_or(0) >= charges[color] {
+                continue;
+            }
+            for cell in standing.iter() {
+                let pos = grid.shoot_pos(*cell);
+                for (dir_index, dir) in dirs.iter().copied().enumerate() {
+                    let beam = world.solve(pos, dir, color);
+                    // toggling crystals can only cut these beams short
+                    let can_charge = beam.hits.iter().any(|hit| {
+                        matches!(
+                            hit,
+                            Some(SolverObject::Sensor(_) | SolverObject::Crystal { .. })
+                        )
+                    });
+                    if !can_charge {
+                        continue;
+                    }
+                    let shot_key = ShotKey {
+                        color,
+                        dir: dir_index,
+                        start: crosses_inactive_crystal(&world, beam.points[0], beam.points[1])
+                            .then(|| pos.round().as_ivec2()),
+                        hits: beam.points[1..]
+                            .iter()
+                            .map(|point| point.round().as_ivec2())
+                            .collect(),
+                    };
+
+                    let mut next_key = key.clone();
+                    next_key.push(shot_key);
+                    if !seen.insert(next_key.clone()) {
+                        continue;
+                    }
+                    let mut next_shots = shots.clone();
+                    next_shots.push(Shot { color, pos, dir });
+                    queue.push_back((next_shots, next_key));
+                }
+            }
+        }
+    }
+    Ok(report)
+}
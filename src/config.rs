@@ -24,6 +24,7 @@ impl Default for Config {
         Config {
             level_config: LevelConfig {
                 level_path: "levels/lightborne.ldtk".into(),
+                palette_path: default_palette_path(),
             },
             debug_config: DebugConfig::default(),
         }
@@ -39,4 +40,11 @@ pub struct DebugConfig {
 #[derive(Deserialize)]
 pub struct LevelConfig {
     pub level_path: String,
+    /// The light palette that defines how each light color behaves
+    #[serde(default = "default_palette_path")]
+    pub palette_path: String,
+}
+
+fn default_palette_path() -> String {
+    "light/default.palette.toml".into()
 }
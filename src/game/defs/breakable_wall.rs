@@ -0,0 +1,204 @@
+use avian2d::prelude::*;
+use bevy::{prelude::*, time::Stopwatch};
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{
+    game::{
+        defs::merge_tile::{spawn_merged_tiles, MergedTile},
+        light::{segments::simulate_light_sources, HitByLight, LightColor, LightHits},
+        lighting::{Occluder2d, Occluder2dDisabled},
+        particle::dust::{DebrisExplosionEvent, DustSurface},
+        Layers, LevelSystems,
+    },
+    ldtk::{LdtkLevelParam, LevelExt},
+    shared::ResetLevels,
+};
+
+pub struct BreakableWallPlugin;
+
+impl Plugin for BreakableWallPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_int_cell_for_layer::<CrackedWallBundle>("Terrain", ANY_CRACKED_CELL);
+        for i in COLORED_CRACKED_CELLS {
+            app.register_ldtk_int_cell_for_layer::<CrackedWallBundle>("Terrain", i);
+        }
+        app.add_systems(
+            PreUpdate,
+            spawn_merged_tiles::<CrackedWall>.in_set(LevelSystems::Processing),
+        );
+        app.add_systems(
+            FixedUpdate,
+            heat_cracked_walls
+                .after(simulate_light_sources)
+                .in_set(LevelSystems::Simulation),
+        );
+        app.add_observer(reset_cracked_walls);
+    }
+}
+
+/// Terrain intgrid value of cracked walls that any beam breaks.
+const ANY_CRACKED_CELL: i32 = 37;
+/// Terrain intgrid values of cracked walls that only beams of one color break, in the order of
+/// [`CRACKED_COLORS`].
+const COLORED_CRACKED_CELLS: std::ops::RangeInclusive<i32> = 38..=41;
+const CRACKED_COLORS: [LightColor; 4] = [
+    LightColor::Green,
+    LightColor::Purple,
+    LightColor::White,
+    LightColor::Blue,
+];
+
+/// How long beams need to hit a cracked wall for it to break, in levels without a
+/// `WallBreakTime`.
+const DEFAULT_WALL_BREAK_SECS: f32 = 1.5;
+
+/// [`Component`] for a single cracked wall intgrid cell. Neighboring cells that break with the same
+/// color are merged into one [`CrackedWallGroup`].
+#[derive(Default, Component, Debug, Clone, Copy)]
+pub struct CrackedWall {
+    /// The color of the beams that heat the wall, or [`None`] if any beam does
+    pub color: Option<LightColor>,
+}
+
+impl From<IntGridCell> for CrackedWall {
+    fn from(cell: IntGridCell) -> Self {
+        let color = COLORED_CRACKED_CELLS
+            .contains(&cell.value)
+            .then(|| CRACKED_COLORS[(cell.value - COLORED_CRACKED_CELLS.start()) as usize]);
+        CrackedWall { color }
+    }
+}
+
+#[derive(Bundle, LdtkIntCell)]
+pub struct CrackedWallBundle {
+    #[from_int_grid_cell]
+    cracked_wall: CrackedWall,
+}
+
+/// [`Component`] on merged cracked walls. The wall heats up while beams that can break it hit it,
+/// and breaks once it has been hit for the level's `WallBreakTime`.
+#[derive(Component, Debug)]
+pub struct CrackedWallGroup {
+    pub color: Option<LightColor>,
+    /// Number of light beams of each color hitting the wall
+    hit_by: LightHits,
+    /// The cumulative time beams that can break the wall have been hitting it
+    exposure: Stopwatch,
+    pub broken: bool,
+}
+
+impl CrackedWallGroup {
+    fn is_heated(&self) -> bool {
+        match self.color {
+            Some(color) => self.hit_by[color] > 0,
+            None => self.hit_by.any_light(),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.hit_by = LightHits::default();
+        self.exposure.reset();
+        self.broken = false;
+    }
+}
+
+impl MergedTile for CrackedWall {
+    type CompareData = Option<LightColor>;
+
+    fn bundle(
+        commands: &mut EntityCommands,
+        center: Vec2,
+        extent: Vec2,
+        compare_data: &Self::CompareData,
+    ) {
+        commands
+            .insert(Collider::rectangle(extent.x, extent.y))
+            .insert(Occluder2d::new(extent.x / 2., extent.y / 2.))
+            .insert(DustSurface::Wall)
+            .insert(Friction::new(0.))
+            .insert(CollisionLayers::new(
+                Layers::Terrain,
+                [Layers::PlayerCollider, Layers::LightRay],
+            ))
+            .insert(Sprite::from_color(CRACKED_WALL_COLOR, extent))
+            .insert(Transform::from_xyz(center.x, center.y, 0.))
+            .insert(CrackedWallGroup {
+                color: *compare_data,
+                hit_by: LightHits::default(),
+                exposure: Stopwatch::default(),
+                broken: false,
+            })
+            .observe(
+                |event: On<HitByLight>, mut q_walls: Query<&mut CrackedWallGroup>| {
+                    let Ok(mut wall) = q_walls.get_mut(event.entity) else {
+                        return;
+                    };
+                    wall.hit_by.apply(&event);
+                },
+            );
+    }
+
+    fn compare_data(&self) -> Self::CompareData {
+        self.color
+    }
+}
+
+const CRACKED_WALL_COLOR: Color = Color::srgb(0.35, 0.3, 0.3);
+/// The color a cracked wall glows right before it breaks.
+const HOT_WALL_COLOR: Color = Color::srgb(2.0, 0.8, 0.3);
+
+/// [`System`] that heats up the [`CrackedWallGroup`]s hit by beams, breaking them once they have
+/// been hit for long enough.
+pub fn heat_cracked_walls(
+    mut commands: Commands,
+    mut q_walls: Query<(Entity, &mut CrackedWallGroup, &mut Sprite, &Transform)>,
+    mut ev_debris: MessageWriter<DebrisExplosionEvent>,
+    ldtk_level_param: LdtkLevelParam,
+    time: Res<Time>,
+) {
+    let break_secs = ldtk_level_param
+        .cur_level()
+        .and_then(|level| level.raw().wall_break_time())
+        .unwrap_or(DEFAULT_WALL_BREAK_SECS);
+
+    for (entity, mut wall, mut sprite, transform) in q_walls.iter_mut() {
+        if wall.broken || !wall.is_heated() {
+            continue;
+        }
+        wall.exposure.tick(time.delta());
+        let heat = (wall.exposure.elapsed_secs() / break_secs).min(1.);
+        sprite.color = CRACKED_WALL_COLOR.mix(&HOT_WALL_COLOR, heat);
+
+        if heat < 1. {
+            continue;
+        }
+        wall.broken = true;
+        commands
+            .entity(entity)
+            .insert(ColliderDisabled)
+            .insert(Occluder2dDisabled)
+            .insert(Visibility::Hidden);
+        let size = sprite.custom_size.unwrap_or_default();
+        ev_debris.write(DebrisExplosionEvent {
+            rect: Rect::from_center_size(transform.translation.truncate(), size),
+        });
+    }
+}
+
+pub fn reset_cracked_walls(
+    _: On<ResetLevels>,
+    mut commands: Commands,
+    mut q_walls: Query<(Entity, &mut CrackedWallGroup, &mut Sprite)>,
+) {
+    for (entity, mut wall, mut sprite) in q_walls.iter_mut() {
+        if wall.broken {
+            commands
+                .entity(entity)
+                .remove::<ColliderDisabled>()
+                .remove::<Occluder2dDisabled>()
+                .insert(Visibility::Inherited);
+        }
+        wall.reset();
+        sprite.color = CRACKED_WALL_COLOR;
+    }
+}
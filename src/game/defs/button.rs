@@ -0,0 +1,292 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{
+    asset::LoadResource,
+    game::{
+        defs::{
+            crystal::{CrystalColor, CrystalToggleEvent},
+            sensor::ButtonColor,
+            signal::{signal_id_field, SetLevelSignal},
+        },
+        lighting::LineLight2d,
+        lyra::{controller::update_grounded, Lyra},
+        particle::spark::SparkExplosionEvent,
+        Layers, LevelSystems,
+    },
+    shared::ResetLevels,
+};
+
+pub struct PressureButtonPlugin;
+
+impl Plugin for PressureButtonPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ButtonAssets>();
+        app.load_resource::<ButtonAssets>();
+        app.register_ldtk_entity::<PressureButtonBundle>("Button");
+        app.add_observer(hydrate_pressure_button);
+        app.add_observer(reset_pressure_buttons);
+        app.add_systems(
+            FixedUpdate,
+            update_pressure_buttons
+                .after(update_grounded)
+                .in_set(LevelSystems::Simulation),
+        );
+    }
+}
+
+#[derive(Resource, Asset, Reflect, Clone)]
+#[reflect(Resource)]
+pub struct ButtonAssets {
+    #[dependency]
+    button: Handle<Image>,
+    #[dependency]
+    sfx: Handle<AudioSource>,
+}
+
+impl FromWorld for ButtonAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+
+        Self {
+            button: asset_server.load("button.png"),
+            sfx: asset_server.load("sfx/button.wav"),
+        }
+    }
+}
+
+/// Index of the pressed frame in the button sprite sheet.
+const BUTTON_PRESSED_INDEX: usize = 1;
+/// The height of the solid bottom of the button, which Lyra stands on to press it.
+const BUTTON_TOP_HEIGHT: f32 = 4.;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PressureButtonMode {
+    /// Active only while Lyra stands on the button
+    #[default]
+    Hold,
+    /// Every press flips the button between active and inactive
+    Toggle,
+    /// The first press activates the button, which then stays locked until the level resets
+    OneShot,
+}
+
+impl From<&String> for PressureButtonMode {
+    fn from(value: &String) -> Self {
+        match value.as_str() {
+            "Hold" => PressureButtonMode::Hold,
+            "Toggle" => PressureButtonMode::Toggle,
+            "OneShot" => PressureButtonMode::OneShot,
+            _ => panic!("String {} does not represent a PressureButtonMode", value),
+        }
+    }
+}
+
+/// [`Component`] for a button that Lyra presses by standing on it. Like a
+/// [`LightSensor`](super::sensor::LightSensor), it either drives a signal channel or toggles the
+/// crystals of its color.
+#[derive(Component, Debug)]
+pub struct PressureButton {
+    pub mode: PressureButtonMode,
+    /// The color of the crystals to toggle
+    pub toggle_color: CrystalColor,
+    /// The signal channel driven by the button. If set, crystals are not toggled.
+    pub event_id: Option<i32>,
+    /// Active state of the button
+    pub is_active: bool,
+    /// If Lyra is standing on the button
+    stood_on: bool,
+}
+
+impl PressureButton {
+    fn reset(&mut self) {
+        self.is_active = false;
+        self.stood_on = false;
+    }
+
+    fn is_pressed(&self) -> bool {
+        self.stood_on || (self.mode != PressureButtonMode::Hold && self.is_active)
+    }
+}
+
+/// Buttons come from the community projects, where the crystal intgrid values are named by
+/// a different set of colors.
+fn button_crystal_color(value: &String) -> CrystalColor {
+    match value.as_str() {
+        "Red" | "Pink" => CrystalColor::Pink,
+        "Green" => CrystalColor::Red,
+        "White" => CrystalColor::White,
+        "Blue" => CrystalColor::Blue,
+        _ => panic!("String {} does not represent a button color", value),
+    }
+}
+
+impl From<&EntityInstance> for PressureButton {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        let toggle_color = button_crystal_color(
+            entity_instance
+                .get_enum_field("light_color")
+                .expect("light_color needs to be an enum field on all buttons"),
+        );
+
+        let mode = entity_instance
+            .get_enum_field("mode")
+            .map(PressureButtonMode::from)
+            .unwrap_or_default();
+
+        PressureButton {
+            mode,
+            toggle_color,
+            event_id: signal_id_field(entity_instance, &["event_id"]),
+            is_active: false,
+            stood_on: false,
+        }
+    }
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct PressureButtonBundle {
+    #[from_entity_instance]
+    button: PressureButton,
+}
+
+pub fn hydrate_pressure_button(
+    event: On<Add, PressureButton>,
+    mut commands: Commands,
+    q_buttons: Query<&PressureButton>,
+    button_assets: Res<ButtonAssets>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let Ok(button) = q_buttons.get(event.entity) else {
+        return;
+    };
+
+    let texture_atlas_layout = texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
+        UVec2::new(8, 8),
+        2,
+        1,
+        None,
+        None,
+    ));
+
+    let color = ButtonColor::from(button.toggle_color);
+
+    commands
+        .entity(event.entity)
+        .insert(RigidBody::Static)
+        .insert(Collider::compound(vec![(
+            Vec2::new(0., (BUTTON_TOP_HEIGHT - 8.) / 2.),
+            Rotation::default(),
+            Collider::rectangle(8., BUTTON_TOP_HEIGHT),
+        )]))
+        .insert(CollisionLayers::new(
+            Layers::Terrain,
+            [
+                Layers::PlayerCollider,
+                Layers::LightRay,
+                Layers::BlueRay,
+                Layers::WhiteRay,
+            ],
+        ))
+        .insert(Sprite {
+            image: button_assets.button.clone(),
+            texture_atlas: Some(TextureAtlas {
+                layout: texture_atlas_layout,
+                index: 0,
+            }),
+            color,
+            ..default()
+        })
+        .insert(LineLight2d::point(
+            color.to_linear().to_vec3().extend(0.5),
+            20.0,
+            0.01,
+        ));
+}
+
+/// Sends the button's signal, using the same path that light sensors use to toggle crystals.
+fn send_button_signal(
+    commands: &mut Commands,
+    entity: Entity,
+    button: &PressureButton,
+    pos: Vec2,
+    button_assets: &ButtonAssets,
+    ev_spark_explosion: &mut MessageWriter<SparkExplosionEvent>,
+) {
+    match button.event_id {
+        Some(id) => commands.trigger(SetLevelSignal {
+            source: entity,
+            id,
+            active: button.is_active,
+        }),
+        None => commands.trigger(CrystalToggleEvent {
+            color: button.toggle_color,
+        }),
+    }
+    commands.entity(entity).with_child((
+        AudioPlayer::new(button_assets.sfx.clone()),
+        PlaybackSettings::DESPAWN,
+    ));
+    ev_spark_explosion.write(SparkExplosionEvent {
+        pos,
+        color: ButtonColor::from(button.toggle_color),
+    });
+}
+
+fn update_button_sprite(button: &PressureButton, sprite: &mut Sprite) {
+    if let Some(atlas) = sprite.texture_atlas.as_mut() {
+        atlas.index = if button.is_pressed() {
+            BUTTON_PRESSED_INDEX
+        } else {
+            0
+        };
+    }
+}
+
+/// [`System`] that presses the buttons Lyra stands on, and releases the ones she stepped off of.
+/// Buttons are only pressed from above, by Lyra's ground [`ShapeHits`].
+pub fn update_pressure_buttons(
+    mut commands: Commands,
+    lyra: Single<&ShapeHits, With<Lyra>>,
+    mut q_buttons: Query<(Entity, &mut PressureButton, &mut Sprite, &GlobalTransform)>,
+    button_assets: Res<ButtonAssets>,
+    mut ev_spark_explosion: MessageWriter<SparkExplosionEvent>,
+) {
+    for (entity, mut button, mut sprite, transform) in q_buttons.iter_mut() {
+        let stood_on = lyra.iter().any(|hit| hit.entity == entity);
+        if stood_on == button.stood_on {
+            continue;
+        }
+        button.stood_on = stood_on;
+
+        let should_send = match (button.mode, stood_on) {
+            (PressureButtonMode::Hold, _) => button.is_active != stood_on,
+            (PressureButtonMode::Toggle, true) => true,
+            (PressureButtonMode::OneShot, true) => !button.is_active,
+            (_, false) => false,
+        };
+        if should_send {
+            button.is_active = !button.is_active;
+            send_button_signal(
+                &mut commands,
+                entity,
+                &button,
+                transform.translation().xy(),
+                &button_assets,
+                &mut ev_spark_explosion,
+            );
+        }
+        update_button_sprite(&button, &mut sprite);
+    }
+}
+
+pub fn reset_pressure_buttons(
+    _: On<ResetLevels>,
+    mut q_buttons: Query<(&mut PressureButton, &mut Sprite)>,
+) {
+    for (mut button, mut sprite) in q_buttons.iter_mut() {
+        button.reset();
+        update_button_sprite(&button, &mut sprite);
+    }
+}
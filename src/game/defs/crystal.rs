@@ -9,13 +9,14 @@ use crate::{
     game::{
         defs::{
             merge_tile::{spawn_merged_tiles, MergedTile},
+            signal::LevelSignalChanged,
             DangerBox,
         },
         lighting::{Occluder2d, Occluder2dDisabled},
         particle::dust::DustSurface,
         Layers, LevelSystems,
     },
-    ldtk::LdtkLevelParam,
+    ldtk::{EntityLevelParam, LdtkLevelParam},
     shared::ResetLevels,
 };
 // use bevy_ecs_tilemap::tiles::TileTextureIndex;
@@ -45,7 +46,9 @@ impl Plugin for CrystalPlugin {
                 .chain()
                 .in_set(LevelSystems::Processing),
         );
+        app.register_ldtk_entity::<CrystalLinkBundle>("CrystalLink");
         app.add_observer(on_crystal_changed);
+        app.add_observer(on_crystal_link_signal);
         app.add_observer(reset_crystals);
 
         for i in 3..=10 {
@@ -340,3 +343,50 @@ pub fn on_crystal_changed(
         }
     }
 }
+
+/// [`Component`] placed in LDtk to subscribe the crystals of a color to a signal channel. Whenever
+/// the channel changes, the crystals are toggled.
+#[derive(Component, Debug)]
+pub struct CrystalLink {
+    pub event_id: i32,
+    pub toggle_color: CrystalColor,
+}
+
+impl From<&EntityInstance> for CrystalLink {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        let event_id = *entity_instance
+            .get_int_field("event_id")
+            .expect("event_id needs to be an int field on all crystal links");
+
+        let toggle_color: CrystalColor = entity_instance
+            .get_enum_field("toggle_color")
+            .expect("toggle_color needs to be an enum field on all crystal links")
+            .into();
+
+        CrystalLink {
+            event_id,
+            toggle_color,
+        }
+    }
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct CrystalLinkBundle {
+    #[from_entity_instance]
+    link: CrystalLink,
+}
+
+pub fn on_crystal_link_signal(
+    event: On<LevelSignalChanged>,
+    mut commands: Commands,
+    q_links: Query<(Entity, &CrystalLink)>,
+    levels: EntityLevelParam,
+) {
+    for (entity, link) in q_links.iter() {
+        if link.event_id == event.id && levels.level_of(entity) == Some(&event.level) {
+            commands.trigger(CrystalToggleEvent {
+                color: link.toggle_color,
+            });
+        }
+    }
+}
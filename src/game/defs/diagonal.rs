@@ -0,0 +1,135 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::game::{
+    defs::rotatable_mirror::MirrorSurface, lighting::Occluder2d, particle::dust::DustSurface,
+    Layers,
+};
+
+pub struct DiagonalTilePlugin;
+
+impl Plugin for DiagonalTilePlugin {
+    fn build(&self, app: &mut App) {
+        for i in DIAGONAL_TERRAIN_CELLS.chain(DIAGONAL_MIRROR_CELLS) {
+            app.register_ldtk_int_cell_for_layer::<DiagonalTileBundle>("Terrain", i);
+        }
+        app.add_observer(hydrate_diagonal_tile);
+    }
+}
+
+/// Terrain intgrid values of diagonal walls, in the order of [`DiagonalCorner::ALL`].
+const DIAGONAL_TERRAIN_CELLS: std::ops::RangeInclusive<i32> = 27..=30;
+/// Terrain intgrid values of diagonal mirrors, in the order of [`DiagonalCorner::ALL`].
+const DIAGONAL_MIRROR_CELLS: std::ops::RangeInclusive<i32> = 31..=34;
+
+/// The corner of the cell that a diagonal tile fills.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DiagonalCorner {
+    #[default]
+    BottomLeft,
+    BottomRight,
+    TopRight,
+    TopLeft,
+}
+
+impl DiagonalCorner {
+    const ALL: [DiagonalCorner; 4] = [
+        DiagonalCorner::BottomLeft,
+        DiagonalCorner::BottomRight,
+        DiagonalCorner::TopRight,
+        DiagonalCorner::TopLeft,
+    ];
+
+    /// The number of counterclockwise quarter turns from a tile filling the bottom left corner.
+    pub fn quarter_turns(&self) -> u32 {
+        match self {
+            DiagonalCorner::BottomLeft => 0,
+            DiagonalCorner::BottomRight => 1,
+            DiagonalCorner::TopRight => 2,
+            DiagonalCorner::TopLeft => 3,
+        }
+    }
+
+    /// The vertices of the triangle filling this corner of a cell with the given half size.
+    pub fn triangle(&self, half_size: f32) -> [Vec2; 3] {
+        let rotation = Rot2::degrees(90. * self.quarter_turns() as f32);
+        [Vec2::new(-1., -1.), Vec2::new(1., -1.), Vec2::new(-1., 1.)]
+            .map(|vertex| rotation * (vertex * half_size))
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DiagonalKind {
+    #[default]
+    Terrain,
+    Mirror,
+}
+
+/// [`Component`] for a diagonal intgrid cell, with a triangle collider whose hypotenuse is at 45
+/// degrees. Unlike other terrain, diagonal cells are not merged.
+#[derive(Default, Component, Debug, Clone, Copy)]
+pub struct DiagonalTile {
+    pub kind: DiagonalKind,
+    pub corner: DiagonalCorner,
+}
+
+impl From<IntGridCell> for DiagonalTile {
+    fn from(cell: IntGridCell) -> Self {
+        let (kind, first) = if DIAGONAL_TERRAIN_CELLS.contains(&cell.value) {
+            (DiagonalKind::Terrain, *DIAGONAL_TERRAIN_CELLS.start())
+        } else {
+            (DiagonalKind::Mirror, *DIAGONAL_MIRROR_CELLS.start())
+        };
+        DiagonalTile {
+            kind,
+            corner: DiagonalCorner::ALL[(cell.value - first) as usize],
+        }
+    }
+}
+
+#[derive(Bundle, LdtkIntCell)]
+pub struct DiagonalTileBundle {
+    #[from_int_grid_cell]
+    tile: DiagonalTile,
+}
+
+pub fn hydrate_diagonal_tile(
+    event: On<Add, DiagonalTile>,
+    mut commands: Commands,
+    q_tiles: Query<&DiagonalTile>,
+) {
+    let Ok(tile) = q_tiles.get(event.entity) else {
+        return;
+    };
+
+    let [a, b, c] = tile.corner.triangle(4.);
+    let mut entity = commands.entity(event.entity);
+    entity
+        .insert(Collider::triangle(a, b, c))
+        .insert(Occluder2d::triangle(4., 4., tile.corner.quarter_turns()))
+        .insert(Friction::new(0.));
+
+    match tile.kind {
+        DiagonalKind::Terrain => {
+            entity
+                .insert(DustSurface::Wall)
+                .insert(CollisionLayers::new(
+                    Layers::Terrain,
+                    [Layers::PlayerCollider, Layers::LightRay],
+                ));
+        }
+        DiagonalKind::Mirror => {
+            entity.insert(MirrorSurface).insert(CollisionLayers::new(
+                Layers::Terrain,
+                [
+                    Layers::LightRay,
+                    Layers::PlayerHurtbox,
+                    Layers::PlayerCollider,
+                    Layers::BlueRay,
+                    Layers::WhiteRay,
+                ],
+            ));
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::game::{
+    defs::merge_tile::{spawn_merged_tiles, MergedTile},
+    light::{palette::LightPalette, LightColor},
+    Layers, LevelSystems,
+};
+
+pub struct GlassPlugin;
+
+impl Plugin for GlassPlugin {
+    fn build(&self, app: &mut App) {
+        for i in GLASS_FILTER_CELLS.chain(GLASS_CONVERTER_CELLS) {
+            app.register_ldtk_int_cell_for_layer::<GlassBundle>("Terrain", i);
+        }
+        app.add_systems(
+            PreUpdate,
+            spawn_merged_tiles::<Glass>.in_set(LevelSystems::Processing),
+        );
+        app.add_observer(hydrate_glass_group);
+    }
+}
+
+/// Terrain intgrid values of filter glass, in the order of [`GLASS_COLORS`].
+const GLASS_FILTER_CELLS: std::ops::RangeInclusive<i32> = 17..=20;
+/// Terrain intgrid values of converter glass, in the order of [`GLASS_COLORS`].
+const GLASS_CONVERTER_CELLS: std::ops::RangeInclusive<i32> = 21..=24;
+const GLASS_COLORS: [LightColor; 4] = [
+    LightColor::Green,
+    LightColor::Purple,
+    LightColor::White,
+    LightColor::Blue,
+];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GlassKind {
+    /// Only lets beams of its color through
+    #[default]
+    Filter,
+    /// Changes the color of beams that pass through it to its color
+    Converter,
+}
+
+/// [`Component`] for a single glass intgrid cell. Neighboring cells of the same glass are merged
+/// into one [`GlassGroup`] with a single collider.
+#[derive(Default, Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Glass {
+    pub kind: GlassKind,
+    pub color: LightColor,
+}
+
+impl Glass {
+    /// The color of a beam of `color` once it leaves the glass, or [`None`] if the glass blocks
+    /// it.
+    pub fn transmit(&self, color: LightColor) -> Option<LightColor> {
+        match self.kind {
+            GlassKind::Filter => (color == self.color).then_some(color),
+            GlassKind::Converter => Some(self.color),
+        }
+    }
+}
+
+impl From<IntGridCell> for Glass {
+    fn from(cell: IntGridCell) -> Self {
+        let (kind, first) = if GLASS_FILTER_CELLS.contains(&cell.value) {
+            (GlassKind::Filter, *GLASS_FILTER_CELLS.start())
+        } else {
+            (GlassKind::Converter, *GLASS_CONVERTER_CELLS.start())
+        };
+        Glass {
+            kind,
+            color: GLASS_COLORS[(cell.value - first) as usize],
+        }
+    }
+}
+
+#[derive(Bundle, LdtkIntCell)]
+pub struct GlassBundle {
+    #[from_int_grid_cell]
+    glass: Glass,
+}
+
+/// [`Component`] on the merged glass entities that beams interact with in
+/// [`play_light_beam`](crate::game::light::segments::play_light_beam).
+#[derive(Component, Debug)]
+pub struct GlassGroup(pub Glass);
+
+impl MergedTile for Glass {
+    type CompareData = Glass;
+
+    fn bundle(
+        commands: &mut EntityCommands,
+        center: Vec2,
+        extent: Vec2,
+        compare_data: &Self::CompareData,
+    ) {
+        commands
+            .insert(Collider::rectangle(extent.x, extent.y))
+            .insert(Friction::new(0.))
+            .insert(CollisionLayers::new(
+                Layers::Terrain,
+                [
+                    Layers::PlayerCollider,
+                    Layers::LightRay,
+                    Layers::BlueRay,
+                    Layers::WhiteRay,
+                ],
+            ))
+            .insert(Sprite::from_color(Color::NONE, extent))
+            .insert(Transform::from_xyz(center.x, center.y, 0.))
+            .insert(GlassGroup(*compare_data));
+    }
+
+    fn compare_data(&self) -> Self::CompareData {
+        *self
+    }
+}
+
+/// Tints merged glass with the color of its light in the [`LightPalette`].
+pub fn hydrate_glass_group(
+    event: On<Add, GlassGroup>,
+    mut q_glass: Query<(&GlassGroup, &mut Sprite)>,
+    palette: Res<LightPalette>,
+) {
+    let Ok((GlassGroup(glass), mut sprite)) = q_glass.get_mut(event.entity) else {
+        return;
+    };
+    sprite.color = palette[glass.color]
+        .indicator_color()
+        .with_alpha(match glass.kind {
+            GlassKind::Filter => 0.25,
+            GlassKind::Converter => 0.5,
+        });
+}
@@ -1,23 +1,39 @@
 use bevy::prelude::*;
 
 use crate::game::defs::{
-    cruciera::CrucieraPlugin, crystal::CrystalPlugin, decoration::DecorationPlugin,
-    mirror::MirrorPlugin, one_way_platform::OneWayPlatformPlugin, sensor::LightSensorPlugin,
-    shard::CrystalShardPlugin, spikes::SpikesPlugin, terrain::TerrainPlugin,
-    tooltip_sign::TooltipSignPlugin,
+    breakable_wall::BreakableWallPlugin, button::PressureButtonPlugin, cruciera::CrucieraPlugin,
+    crystal::CrystalPlugin, decoration::DecorationPlugin, diagonal::DiagonalTilePlugin,
+    glass::GlassPlugin, mirror::MirrorPlugin, moving_platform::MovingPlatformPlugin,
+    one_way_platform::OneWayPlatformPlugin, phase_block::PhaseBlockPlugin, portal::PortalPlugin,
+    prism::PrismPlugin, refractor::RefractorPlugin, rotatable_mirror::RotatableMirrorPlugin,
+    sensor::LightSensorPlugin, shard::CrystalShardPlugin, signal::LevelSignalPlugin,
+    spikes::SpikesPlugin, terrain::TerrainPlugin, tooltip_sign::TooltipSignPlugin,
+    vine::VinePlugin,
 };
 
+pub mod breakable_wall;
+pub mod button;
 mod cruciera;
 pub mod crystal;
 mod decoration;
+pub mod diagonal;
+pub mod glass;
 mod merge_tile;
 pub mod mirror;
+pub mod moving_platform;
 pub mod one_way_platform;
+pub mod phase_block;
+pub mod portal;
+pub mod prism;
+pub mod refractor;
+pub mod rotatable_mirror;
 pub mod sensor;
 pub mod shard;
+pub mod signal;
 mod spikes;
 mod terrain;
 pub mod tooltip_sign;
+pub mod vine;
 
 pub struct LevelPlugin;
 
@@ -33,6 +49,18 @@ impl Plugin for LevelPlugin {
         app.add_plugins(CrucieraPlugin);
         app.add_plugins(DecorationPlugin);
         app.add_plugins(MirrorPlugin);
+        app.add_plugins(MovingPlatformPlugin);
+        app.add_plugins(LevelSignalPlugin);
+        app.add_plugins(PressureButtonPlugin);
+        app.add_plugins(PrismPlugin);
+        app.add_plugins(GlassPlugin);
+        app.add_plugins(RefractorPlugin);
+        app.add_plugins(RotatableMirrorPlugin);
+        app.add_plugins(DiagonalTilePlugin);
+        app.add_plugins(PortalPlugin);
+        app.add_plugins(PhaseBlockPlugin);
+        app.add_plugins(BreakableWallPlugin);
+        app.add_plugins(VinePlugin);
     }
 }
 
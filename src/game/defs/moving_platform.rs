@@ -0,0 +1,395 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{
+    asset::LoadResource,
+    game::{
+        defs::signal::LevelSignalChanged, lighting::Occluder2d, lyra::controller::movement,
+        particle::dust::DustSurface, Layers, LevelSystems,
+    },
+    ldtk::EntityLevelParam,
+    shared::ResetLevels,
+};
+
+pub struct MovingPlatformPlugin;
+
+impl Plugin for MovingPlatformPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<MovingPlatformAssets>();
+        app.load_resource::<MovingPlatformAssets>();
+        app.register_ldtk_entity::<MovingPlatformBundle>("MovingPlatform");
+        app.add_observer(hydrate_moving_platform);
+        app.add_observer(on_moving_platform_toggle);
+        app.add_observer(reset_moving_platforms);
+        app.add_systems(
+            FixedUpdate,
+            move_platforms
+                .before(movement)
+                .in_set(LevelSystems::Simulation),
+        );
+    }
+}
+
+/// The number of line segments used to approximate each curved part of a platform path.
+const CURVE_RESOLUTION: usize = 12;
+
+#[derive(Resource, Asset, Reflect, Clone)]
+#[reflect(Resource)]
+pub struct MovingPlatformAssets {
+    #[dependency]
+    platform: Handle<Image>,
+}
+
+impl FromWorld for MovingPlatformAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+
+        Self {
+            platform: asset_server.load("moving_platform.png"),
+        }
+    }
+}
+
+/// [`Component`] for a solid platform that moves along a path defined in LDtk. The path is
+/// stored as offsets from the position the platform was spawned at, and is flattened into a
+/// polyline so that curved points can be followed at a constant speed.
+#[derive(Component, Debug)]
+pub struct MovingPlatform {
+    /// Signal channel that starts or stops this platform whenever it changes
+    pub event_id: i32,
+    /// Size of the platform, in pixels
+    size: Vec2,
+    /// Points along the path, relative to the spawn position of the platform
+    path: Vec<Vec2>,
+    /// Distance along the path at each of the points in `path`
+    distances: Vec<f32>,
+    /// Speed of the platform, in pixels per second
+    speed: f32,
+    /// If the platform should travel backwards along the path after reaching the end
+    does_reverse: bool,
+    /// If the platform should keep moving after completing the path once
+    does_repeat: bool,
+    /// If the platform can be restarted by an event after it has finished moving
+    can_reactivate: bool,
+    init_active: bool,
+    active: bool,
+    finished: bool,
+    /// Distance travelled along the path
+    progress: f32,
+    /// Either `1.0` or `-1.0`, depending on the direction the platform travels along the path
+    direction: f32,
+    /// Spawn position of the platform, in the [`Transform`] space of its parent
+    start: Option<Vec2>,
+}
+
+impl MovingPlatform {
+    fn new(
+        size: Vec2,
+        points: &[Vec2],
+        curves: &[bool],
+        speed: f32,
+        does_reverse: bool,
+        does_repeat: bool,
+    ) -> Self {
+        let mut path = build_path(points, curves);
+        // a repeating platform that doesn't reverse loops back to where it started
+        if does_repeat && !does_reverse && path.last() != Some(&Vec2::ZERO) {
+            path.push(Vec2::ZERO);
+        }
+
+        let mut distances = Vec::with_capacity(path.len());
+        let mut total = 0.0;
+        for (i, point) in path.iter().enumerate() {
+            if i > 0 {
+                total += path[i - 1].distance(*point);
+            }
+            distances.push(total);
+        }
+
+        MovingPlatform {
+            event_id: -1,
+            size,
+            path,
+            distances,
+            speed,
+            does_reverse,
+            does_repeat,
+            can_reactivate: false,
+            init_active: true,
+            active: true,
+            finished: false,
+            progress: 0.0,
+            direction: 1.0,
+            start: None,
+        }
+    }
+
+    fn length(&self) -> f32 {
+        self.distances.last().copied().unwrap_or(0.0)
+    }
+
+    fn reset(&mut self) {
+        self.active = self.init_active;
+        self.finished = false;
+        self.progress = 0.0;
+        self.direction = 1.0;
+    }
+
+    /// Toggles the platform between moving and paused. Finished platforms can only be restarted
+    /// if they are allowed to reactivate, in which case they retrace their path.
+    fn toggle(&mut self) {
+        if self.finished {
+            if !self.can_reactivate {
+                return;
+            }
+            self.finished = false;
+            self.direction = -self.direction;
+            self.active = true;
+            return;
+        }
+        self.active = !self.active;
+    }
+
+    /// Moves the platform along its path by `dist` pixels, handling the ends of the path.
+    fn advance(&mut self, mut dist: f32) {
+        let length = self.length();
+        if length <= 0.0 {
+            self.finished = true;
+            return;
+        }
+
+        // the loop handles ends of the path being reached multiple times in a single step
+        while dist > 0.0 && !self.finished {
+            let to_end = if self.direction > 0.0 {
+                length - self.progress
+            } else {
+                self.progress
+            };
+
+            if dist < to_end {
+                self.progress += dist * self.direction;
+                return;
+            }
+            dist -= to_end;
+            self.progress = if self.direction > 0.0 { length } else { 0.0 };
+
+            let at_start = self.direction < 0.0;
+            if self.does_reverse {
+                // a non-repeating platform goes to the end and back exactly once
+                if at_start && !self.does_repeat {
+                    self.finished = true;
+                } else {
+                    self.direction = -self.direction;
+                }
+            } else if self.does_repeat {
+                self.progress = 0.0;
+            } else {
+                self.finished = true;
+            }
+        }
+    }
+
+    /// The offset from the start of the path at the current progress.
+    fn offset(&self) -> Vec2 {
+        let Some(i) = self.distances.iter().position(|d| *d > self.progress) else {
+            return self.path.last().copied().unwrap_or(Vec2::ZERO);
+        };
+        if i == 0 {
+            return self.path[0];
+        }
+        let span = self.distances[i] - self.distances[i - 1];
+        let t = (self.progress - self.distances[i - 1]) / span;
+        self.path[i - 1].lerp(self.path[i], t)
+    }
+}
+
+/// Flattens the LDtk path into a polyline that starts at the origin. Points marked as curve
+/// points are treated as the control point of a quadratic bezier between their neighbors.
+fn build_path(points: &[Vec2], curves: &[bool]) -> Vec<Vec2> {
+    let mut path = vec![Vec2::ZERO];
+    let mut i = 0;
+    while i < points.len() {
+        let is_curve = curves.get(i).copied().unwrap_or(false);
+        if is_curve && i + 1 < points.len() {
+            let from = *path.last().expect("path is never empty");
+            let control = points[i];
+            let to = points[i + 1];
+            for step in 1..=CURVE_RESOLUTION {
+                let t = step as f32 / CURVE_RESOLUTION as f32;
+                path.push(from.lerp(control, t).lerp(control.lerp(to, t), t));
+            }
+            i += 2;
+        } else {
+            path.push(points[i]);
+            i += 1;
+        }
+    }
+    path
+}
+
+#[derive(Bundle)]
+pub struct MovingPlatformBundle {
+    platform: MovingPlatform,
+}
+
+impl LdtkEntity for MovingPlatformBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        layer_instance: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlasLayout>,
+    ) -> Self {
+        let grid_size = layer_instance.grid_size as f32;
+        let origin = entity_instance.grid;
+
+        // NOTE: path points are in ldtk grid coordinates, so flip the y value
+        let points: Vec<Vec2> = entity_instance
+            .get_maybe_points_field("path")
+            .expect("path needs to be a points field on all moving platforms")
+            .iter()
+            .flatten()
+            .map(|point| {
+                Vec2::new(
+                    (point.x - origin.x) as f32 * grid_size,
+                    -(point.y - origin.y) as f32 * grid_size,
+                )
+            })
+            .collect();
+
+        let curves = entity_instance
+            .get_bools_field("path_curve_points")
+            .map(|curves| curves.to_vec())
+            .unwrap_or_default();
+
+        let speed = *entity_instance
+            .get_float_field("speed")
+            .expect("speed needs to be a float field on all moving platforms");
+        let does_reverse = *entity_instance
+            .get_bool_field("does_reverse")
+            .expect("does_reverse needs to be a bool field on all moving platforms");
+        let does_repeat = *entity_instance
+            .get_bool_field("does_repeat")
+            .expect("does_repeat needs to be a bool field on all moving platforms");
+        let can_reactivate = entity_instance
+            .get_bool_field("can_reactivate")
+            .is_ok_and(|val| *val);
+        let event_id = *entity_instance
+            .get_int_field("event_id")
+            .expect("event_id needs to be an int field on all moving platforms");
+
+        // platforms without an event can never be started, so they play by default
+        let init_active = match entity_instance.get_enum_field("DefaultState") {
+            Ok(state) => state == "Play",
+            Err(_) => event_id < 0,
+        };
+
+        let mut platform = MovingPlatform::new(
+            Vec2::new(entity_instance.width as f32, entity_instance.height as f32),
+            &points,
+            &curves,
+            speed,
+            does_reverse,
+            does_repeat,
+        );
+        platform.event_id = event_id;
+        platform.can_reactivate = can_reactivate;
+        platform.init_active = init_active;
+        platform.active = init_active;
+
+        Self { platform }
+    }
+}
+
+pub fn hydrate_moving_platform(
+    event: On<Add, MovingPlatform>,
+    mut commands: Commands,
+    q_platforms: Query<&MovingPlatform>,
+    platform_assets: Res<MovingPlatformAssets>,
+) {
+    let Ok(platform) = q_platforms.get(event.entity) else {
+        return;
+    };
+
+    commands
+        .entity(event.entity)
+        .insert(RigidBody::Kinematic)
+        .insert(Collider::rectangle(platform.size.x, platform.size.y))
+        .insert(Occluder2d::new(platform.size.x / 2., platform.size.y / 2.))
+        .insert(DustSurface::Wood)
+        .insert(Friction::new(0.))
+        .insert(CollisionLayers::new(
+            Layers::Terrain,
+            [
+                Layers::PlayerCollider,
+                Layers::LightRay,
+                Layers::BlueRay,
+                Layers::WhiteRay,
+            ],
+        ))
+        .insert(Sprite {
+            image: platform_assets.platform.clone(),
+            custom_size: Some(platform.size),
+            ..default()
+        });
+}
+
+pub fn on_moving_platform_toggle(
+    event: On<LevelSignalChanged>,
+    mut q_platforms: Query<(Entity, &mut MovingPlatform)>,
+    levels: EntityLevelParam,
+) {
+    for (entity, mut platform) in q_platforms.iter_mut() {
+        if platform.event_id == event.id && levels.level_of(entity) == Some(&event.level) {
+            platform.toggle();
+        }
+    }
+}
+
+pub fn reset_moving_platforms(
+    _: On<ResetLevels>,
+    mut q_platforms: Query<(
+        &mut MovingPlatform,
+        &mut Transform,
+        &mut Position,
+        &mut LinearVelocity,
+    )>,
+) {
+    for (mut platform, mut transform, mut position, mut velocity) in q_platforms.iter_mut() {
+        platform.reset();
+        let Some(start) = platform.start else {
+            continue;
+        };
+        let delta = start - transform.translation.xy();
+        transform.translation += delta.extend(0.);
+        position.0 += delta;
+        velocity.0 = Vec2::ZERO;
+    }
+}
+
+/// [`System`] that advances [`MovingPlatform`]s along their path. Platforms are kinematic bodies,
+/// so they are moved by setting their [`LinearVelocity`] such that they reach their target
+/// position by the end of the physics step. This lets the physics engine push and carry Lyra.
+pub fn move_platforms(
+    mut q_platforms: Query<(&mut MovingPlatform, &Transform, &mut LinearVelocity)>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+
+    for (mut platform, transform, mut velocity) in q_platforms.iter_mut() {
+        let start = *platform.start.get_or_insert(transform.translation.xy());
+
+        if platform.active && !platform.finished {
+            let speed = platform.speed;
+            platform.advance(speed * dt);
+        }
+
+        let target = start + platform.offset();
+        velocity.0 = (target - transform.translation.xy()) / dt;
+    }
+}
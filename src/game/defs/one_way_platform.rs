@@ -165,10 +165,16 @@ impl CollisionHooks for OneWayPlatformHooks<'_, '_> {
             // Default behaviour is "by normal".
             Ok((other_transform, PassThroughOneWayPlatform::ByNormal)) => {
                 const PLAYER_HALF_HEIGHT: f32 = 10.25;
-                if other_transform.compute_transform().translation.y
-                    - platform_transform.compute_transform().translation.y
-                    > PLAYER_HALF_HEIGHT
-                {
+                // platforms can be sloped (e.g. light bridges), so compare against the height of
+                // the platform under the other entity. Platforms steeper than 45 degrees can't be
+                // stood on.
+                let platform = platform_transform.compute_transform();
+                let other = other_transform.compute_transform().translation;
+                let tangent = platform.rotation * Vec3::X;
+                let slope = tangent.y / tangent.x;
+                let height =
+                    other.y - (platform.translation.y + slope * (other.x - platform.translation.x));
+                if slope.abs() <= 1. && height > PLAYER_HALF_HEIGHT {
                     true
                 } else {
                     // Otherwise, ignore the collision and register
@@ -0,0 +1,222 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{
+    game::{
+        defs::merge_tile::{spawn_merged_tiles, MergedTile},
+        light::{HitByLight, LightHits},
+        lighting::{Occluder2d, Occluder2dDisabled},
+        Layers, LevelSystems,
+    },
+    shared::ResetLevels,
+};
+
+pub struct PhaseBlockPlugin;
+
+impl Plugin for PhaseBlockPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_int_cell_for_layer::<PhaseBlockBundle>("Terrain", LIT_PHASE_CELL);
+        app.register_ldtk_int_cell_for_layer::<PhaseBlockBundle>("Terrain", DARK_PHASE_CELL);
+        app.add_systems(
+            PreUpdate,
+            spawn_merged_tiles::<PhaseBlock>.in_set(LevelSystems::Processing),
+        );
+        app.add_observer(reset_phase_blocks);
+    }
+}
+
+/// Terrain intgrid value of phase blocks that are solid while lit.
+const LIT_PHASE_CELL: i32 = 35;
+/// Terrain intgrid value of phase blocks that are solid while dark.
+const DARK_PHASE_CELL: i32 = 36;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PhaseKind {
+    /// Solid only while a beam touches it
+    #[default]
+    Lit,
+    /// Solid only while no beam touches it
+    Dark,
+}
+
+/// [`Component`] for a single phase block intgrid cell. Neighboring cells of the same kind are
+/// merged into one [`PhaseBlockGroup`].
+#[derive(Default, Component, Debug, Clone, Copy)]
+pub struct PhaseBlock {
+    pub kind: PhaseKind,
+}
+
+impl From<IntGridCell> for PhaseBlock {
+    fn from(cell: IntGridCell) -> Self {
+        PhaseBlock {
+            kind: match cell.value {
+                LIT_PHASE_CELL => PhaseKind::Lit,
+                DARK_PHASE_CELL => PhaseKind::Dark,
+                _ => panic!("Cell value does not correspond to phase block!"),
+            },
+        }
+    }
+}
+
+#[derive(Bundle, LdtkIntCell)]
+pub struct PhaseBlockBundle {
+    #[from_int_grid_cell]
+    phase_block: PhaseBlock,
+}
+
+/// [`Component`] on merged phase blocks. Beams always hit the group itself, which triggers
+/// [`HitByLight`] like it does for sensors, while Lyra collides with its [`PhaseBlockSolid`] child
+/// that is only enabled while the group is solid.
+#[derive(Component, Debug)]
+pub struct PhaseBlockGroup {
+    pub kind: PhaseKind,
+    /// Number of light beams of each color hitting the block
+    hit_by: LightHits,
+    solid: Entity,
+}
+
+impl PhaseBlockGroup {
+    pub fn is_solid(&self) -> bool {
+        match self.kind {
+            PhaseKind::Lit => self.hit_by.is_lit(),
+            PhaseKind::Dark => !self.hit_by.is_lit(),
+        }
+    }
+
+    fn color(&self) -> Color {
+        let color = match self.kind {
+            PhaseKind::Lit => Color::srgb(1.0, 0.9, 0.6),
+            PhaseKind::Dark => Color::srgb(0.4, 0.3, 0.6),
+        };
+        color.with_alpha(if self.is_solid() { 0.8 } else { 0.2 })
+    }
+}
+
+/// [`Component`] on the child of a [`PhaseBlockGroup`] that Lyra collides with.
+#[derive(Component, Debug)]
+pub struct PhaseBlockSolid;
+
+impl MergedTile for PhaseBlock {
+    type CompareData = PhaseKind;
+
+    fn bundle(
+        commands: &mut EntityCommands,
+        center: Vec2,
+        extent: Vec2,
+        compare_data: &Self::CompareData,
+    ) {
+        let group = commands.id();
+        let solid = commands
+            .commands()
+            .spawn((
+                PhaseBlockSolid,
+                Collider::rectangle(extent.x, extent.y),
+                CollisionLayers::new(Layers::PhaseBlock, Layers::PlayerCollider),
+                Friction::new(0.),
+                Transform::default(),
+                ChildOf(group),
+            ))
+            .id();
+
+        let phase_block = PhaseBlockGroup {
+            kind: *compare_data,
+            hit_by: LightHits::default(),
+            solid,
+        };
+
+        commands
+            .insert(Collider::rectangle(extent.x, extent.y))
+            .insert(CollisionLayers::new(
+                Layers::LightSensor,
+                [Layers::LightRay, Layers::WhiteRay, Layers::BlueRay],
+            ))
+            .insert(Occluder2d::new(extent.x / 2., extent.y / 2.))
+            .insert(Sprite::from_color(phase_block.color(), extent))
+            .insert(Transform::from_xyz(center.x, center.y, 0.))
+            .observe(on_phase_block_hit);
+
+        if !phase_block.is_solid() {
+            commands.insert(Occluder2dDisabled);
+            commands.commands().entity(solid).insert(ColliderDisabled);
+        }
+        commands.insert(phase_block);
+    }
+
+    fn compare_data(&self) -> Self::CompareData {
+        self.kind
+    }
+}
+
+/// Makes a [`PhaseBlockGroup`] solid or intangible to match its [`PhaseBlockGroup::is_solid`].
+fn apply_phase(
+    commands: &mut Commands,
+    entity: Entity,
+    phase_block: &PhaseBlockGroup,
+    sprite: &mut Sprite,
+) {
+    sprite.color = phase_block.color();
+    if phase_block.is_solid() {
+        commands.entity(entity).remove::<Occluder2dDisabled>();
+        commands
+            .entity(phase_block.solid)
+            .remove::<ColliderDisabled>();
+    } else {
+        commands.entity(entity).insert(Occluder2dDisabled);
+        commands.entity(phase_block.solid).insert(ColliderDisabled);
+    }
+}
+
+pub fn on_phase_block_hit(
+    event: On<HitByLight>,
+    mut commands: Commands,
+    mut q_phase_blocks: Query<(&mut PhaseBlockGroup, &mut Sprite)>,
+) {
+    let Ok((mut phase_block, mut sprite)) = q_phase_blocks.get_mut(event.entity) else {
+        return;
+    };
+    let was_solid = phase_block.is_solid();
+    phase_block.hit_by.apply(&event);
+    if phase_block.is_solid() != was_solid {
+        apply_phase(&mut commands, event.entity, &phase_block, &mut sprite);
+    }
+}
+
+pub fn reset_phase_blocks(
+    _: On<ResetLevels>,
+    mut commands: Commands,
+    mut q_phase_blocks: Query<(Entity, &mut PhaseBlockGroup, &mut Sprite)>,
+) {
+    for (entity, mut phase_block, mut sprite) in q_phase_blocks.iter_mut() {
+        phase_block.hit_by = LightHits::default();
+        apply_phase(&mut commands, entity, &phase_block, &mut sprite);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::light::LightColor;
+
+    #[test]
+    fn phase_blocks_follow_light() {
+        let mut lit = PhaseBlockGroup {
+            kind: PhaseKind::Lit,
+            hit_by: LightHits::default(),
+            solid: Entity::PLACEHOLDER,
+        };
+        assert!(!lit.is_solid());
+        lit.hit_by[LightColor::Green] = 1;
+        assert!(lit.is_solid());
+        // black light keeps it dark
+        lit.hit_by[LightColor::Black] = 1;
+        assert!(!lit.is_solid());
+
+        let dark = PhaseBlockGroup {
+            kind: PhaseKind::Dark,
+            hit_by: lit.hit_by,
+            solid: Entity::PLACEHOLDER,
+        };
+        assert!(dark.is_solid());
+    }
+}
@@ -0,0 +1,151 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::game::{lighting::LineLight2d, Layers};
+
+pub struct PortalPlugin;
+
+impl Plugin for PortalPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_entity::<PortalBundle>("Portal");
+        app.add_observer(hydrate_portal);
+    }
+}
+
+const PORTAL_THICKNESS: f32 = 2.0;
+
+/// [`Component`] for one end of a portal pair. Beams that hit the front of a portal leave the
+/// other portal with the same `pair_id`, and hitting the back of a portal is like hitting a wall.
+#[derive(Component, Debug)]
+pub struct Portal {
+    pub pair_id: i32,
+    /// Angle of the direction the portal faces, in radians
+    pub angle: f32,
+    pub length: f32,
+}
+
+impl Portal {
+    pub fn normal(&self) -> Dir2 {
+        Rot2::radians(self.angle) * Dir2::X
+    }
+
+    /// Where a beam that hits this portal at `point` going in `dir` leaves `exit`, or [`None`] if
+    /// it hit the back of the portal. The beam keeps its offset along the portal and its angle to
+    /// the portal's normal.
+    pub fn transport(
+        &self,
+        center: Vec2,
+        exit: &Portal,
+        exit_center: Vec2,
+        point: Vec2,
+        dir: Dir2,
+    ) -> Option<(Vec2, Dir2)> {
+        if dir.dot(*self.normal()) >= 0. {
+            return None;
+        }
+        // turns the direction into this portal to the direction out of the exit
+        let rotation = Rot2::radians(exit.angle - self.angle + PI);
+        let tangent = Rot2::radians(FRAC_PI_2) * self.normal();
+        let along = (point - center)
+            .dot(*tangent)
+            .clamp(-exit.length / 2., exit.length / 2.);
+        Some((exit_center + rotation * (*tangent * along), rotation * dir))
+    }
+}
+
+impl From<&EntityInstance> for Portal {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        let pair_id = *entity_instance
+            .get_int_field("pair_id")
+            .expect("pair_id needs to be an int field on all portals");
+        let angle = entity_instance
+            .get_float_field("angle")
+            .copied()
+            .unwrap_or(0.);
+
+        Portal {
+            pair_id,
+            angle: angle.to_radians(),
+            length: entity_instance.height as f32,
+        }
+    }
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct PortalBundle {
+    #[from_entity_instance]
+    portal: Portal,
+}
+
+pub fn hydrate_portal(event: On<Add, Portal>, mut commands: Commands, q_portals: Query<&Portal>) {
+    let Ok(portal) = q_portals.get(event.entity) else {
+        return;
+    };
+
+    let rotation = Rotation::radians(portal.angle);
+    let color = Color::srgb(0.7, 0.4, 1.0);
+
+    commands
+        .entity(event.entity)
+        .insert(Collider::compound(vec![(
+            Vec2::ZERO,
+            rotation,
+            Collider::rectangle(PORTAL_THICKNESS, portal.length),
+        )]))
+        .insert(CollisionLayers::new(
+            Layers::Portal,
+            [Layers::LightRay, Layers::BlueRay, Layers::WhiteRay],
+        ))
+        .insert(LineLight2d::point(
+            color.to_linear().to_vec3().extend(0.5),
+            20.0,
+            0.01,
+        ))
+        .with_child((
+            Sprite::from_color(color, Vec2::new(PORTAL_THICKNESS, portal.length)),
+            Transform::from_rotation(Quat::from_rotation_z(portal.angle)),
+        ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beam_leaves_exit_portal_at_same_angle() {
+        let entry = Portal {
+            pair_id: 0,
+            angle: 0.,
+            length: 16.,
+        };
+        // faces up, at (100, 0)
+        let exit = Portal {
+            pair_id: 0,
+            angle: FRAC_PI_2,
+            length: 16.,
+        };
+
+        let dir = Dir2::new(Vec2::new(-1., 1.)).unwrap();
+        let (pos, out) = entry
+            .transport(
+                Vec2::ZERO,
+                &exit,
+                Vec2::new(100., 0.),
+                Vec2::new(0., 4.),
+                dir,
+            )
+            .unwrap();
+
+        assert!(pos.distance(Vec2::new(104., 0.)) < 1e-4);
+        assert!(out.dot(*exit.normal()) > 0.);
+        assert!((out.dot(*exit.normal()) + dir.dot(*entry.normal())).abs() < 1e-5);
+
+        // the back of the portal doesn't lead anywhere
+        assert!(entry
+            .transport(Vec2::ZERO, &exit, Vec2::new(100., 0.), Vec2::ZERO, -dir)
+            .is_none());
+    }
+}
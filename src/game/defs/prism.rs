@@ -0,0 +1,131 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{
+    asset::LoadResource,
+    game::{light::LightColor, Layers},
+};
+
+pub struct PrismPlugin;
+
+impl Plugin for PrismPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<PrismAssets>();
+        app.load_resource::<PrismAssets>();
+        app.register_ldtk_entity::<PrismBundle>("Prism");
+        app.add_observer(hydrate_prism);
+    }
+}
+
+/// The default angle between the branches of a split beam, in degrees.
+const PRISM_DEFAULT_ANGLE: f32 = 30.0;
+
+#[derive(Resource, Asset, Reflect, Clone)]
+#[reflect(Resource)]
+pub struct PrismAssets {
+    #[dependency]
+    prism: Handle<Image>,
+}
+
+impl FromWorld for PrismAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+
+        Self {
+            prism: asset_server.load("prism.png"),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PrismKind {
+    /// Splits white light into green, purple and blue. Other colors pass straight through.
+    #[default]
+    Spectrum,
+    /// Splits any light into two beams of the same color
+    Fork,
+}
+
+impl From<&String> for PrismKind {
+    fn from(value: &String) -> Self {
+        match value.as_str() {
+            "Spectrum" => PrismKind::Spectrum,
+            "Fork" => PrismKind::Fork,
+            _ => panic!("String {} does not represent a PrismKind", value),
+        }
+    }
+}
+
+/// [`Component`] for a prism that splits the light beams hitting it. Beams stop at the prism, and
+/// continue as the branches returned by [`Prism::split`].
+#[derive(Component, Debug)]
+pub struct Prism {
+    pub kind: PrismKind,
+    /// Angle of the outer branches from the incoming beam, in radians
+    pub angle: f32,
+}
+
+impl Prism {
+    /// The color and direction of each beam leaving the prism, when it is hit by a beam of `color`
+    /// travelling in `dir`.
+    pub fn split(&self, color: LightColor, dir: Dir2) -> Vec<(LightColor, Dir2)> {
+        let left = Rot2::radians(self.angle) * dir;
+        let right = Rot2::radians(-self.angle) * dir;
+        match self.kind {
+            PrismKind::Spectrum if color == LightColor::White => vec![
+                (LightColor::Green, left),
+                (LightColor::Purple, dir),
+                (LightColor::Blue, right),
+            ],
+            PrismKind::Spectrum => vec![(color, dir)],
+            PrismKind::Fork => vec![(color, left), (color, right)],
+        }
+    }
+}
+
+impl From<&EntityInstance> for Prism {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        let kind = entity_instance
+            .get_enum_field("kind")
+            .map(PrismKind::from)
+            .unwrap_or_default();
+
+        let angle = entity_instance
+            .get_float_field("angle")
+            .copied()
+            .unwrap_or(PRISM_DEFAULT_ANGLE);
+
+        Prism {
+            kind,
+            angle: angle.to_radians(),
+        }
+    }
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct PrismBundle {
+    #[from_entity_instance]
+    prism: Prism,
+}
+
+pub fn hydrate_prism(
+    event: On<Add, Prism>,
+    mut commands: Commands,
+    prism_assets: Res<PrismAssets>,
+) {
+    commands
+        .entity(event.entity)
+        .insert(Collider::rectangle(8., 8.))
+        .insert(Friction::new(0.))
+        .insert(CollisionLayers::new(
+            Layers::Terrain,
+            [
+                Layers::LightRay,
+                Layers::PlayerCollider,
+                Layers::BlueRay,
+                Layers::WhiteRay,
+            ],
+        ))
+        .insert(Sprite::from_image(prism_assets.prism.clone()));
+}
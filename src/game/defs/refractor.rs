@@ -0,0 +1,128 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::game::{
+    defs::merge_tile::{spawn_merged_tiles, MergedTile},
+    Layers, LevelSystems,
+};
+
+pub struct RefractorPlugin;
+
+impl Plugin for RefractorPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_int_cell_for_layer::<RefractorBundle>("Terrain", LENS_CELL);
+        app.register_ldtk_int_cell_for_layer::<RefractorBundle>("Terrain", WATER_CELL);
+        app.add_systems(
+            PreUpdate,
+            spawn_merged_tiles::<Refractor>.in_set(LevelSystems::Processing),
+        );
+    }
+}
+
+/// Terrain intgrid value of lens glass.
+const LENS_CELL: i32 = 25;
+/// Terrain intgrid value of water.
+const WATER_CELL: i32 = 26;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RefractiveMedium {
+    /// Solid glass that bends beams strongly
+    #[default]
+    Lens,
+    /// Water that Lyra can move through
+    Water,
+}
+
+impl RefractiveMedium {
+    pub fn refractive_index(&self) -> f32 {
+        match self {
+            RefractiveMedium::Lens => 1.5,
+            RefractiveMedium::Water => 1.33,
+        }
+    }
+}
+
+/// [`Component`] for a single refractive intgrid cell. Neighboring cells of the same medium are
+/// merged into one [`RefractorGroup`] with a single collider.
+#[derive(Default, Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Refractor {
+    pub medium: RefractiveMedium,
+}
+
+impl From<IntGridCell> for Refractor {
+    fn from(cell: IntGridCell) -> Self {
+        let medium = match cell.value {
+            WATER_CELL => RefractiveMedium::Water,
+            _ => RefractiveMedium::Lens,
+        };
+        Refractor { medium }
+    }
+}
+
+#[derive(Bundle, LdtkIntCell)]
+pub struct RefractorBundle {
+    #[from_int_grid_cell]
+    refractor: Refractor,
+}
+
+/// [`Component`] on the merged refractive volumes that bend beams in
+/// [`play_light_beam`](crate::game::light::segments::play_light_beam).
+#[derive(Component, Debug)]
+pub struct RefractorGroup {
+    pub medium: RefractiveMedium,
+    pub refractive_index: f32,
+}
+
+impl MergedTile for Refractor {
+    type CompareData = RefractiveMedium;
+
+    fn bundle(
+        commands: &mut EntityCommands,
+        center: Vec2,
+        extent: Vec2,
+        compare_data: &Self::CompareData,
+    ) {
+        // water is not solid, so it gets its own layer to keep it out of Lyra's ground casts
+        let (layers, tint) = match compare_data {
+            RefractiveMedium::Lens => (
+                CollisionLayers::new(
+                    Layers::Terrain,
+                    [
+                        Layers::PlayerCollider,
+                        Layers::LightRay,
+                        Layers::BlueRay,
+                        Layers::WhiteRay,
+                    ],
+                ),
+                Color::srgba(0.9, 0.95, 1.0, 0.3),
+            ),
+            RefractiveMedium::Water => (
+                CollisionLayers::new(
+                    Layers::Water,
+                    [Layers::LightRay, Layers::BlueRay, Layers::WhiteRay],
+                ),
+                Color::srgba(0.2, 0.45, 0.9, 0.4),
+            ),
+        };
+
+        commands
+            .insert(Collider::rectangle(extent.x, extent.y))
+            .insert(Friction::new(0.))
+            .insert(layers)
+            .insert(Sprite::from_color(tint, extent))
+            .insert(Transform::from_xyz(center.x, center.y, 0.))
+            .insert(RefractorGroup {
+                medium: *compare_data,
+                refractive_index: compare_data.refractive_index(),
+            });
+
+        if *compare_data == RefractiveMedium::Water {
+            commands.insert(Sensor);
+        }
+    }
+
+    fn compare_data(&self) -> Self::CompareData {
+        self.medium
+    }
+}
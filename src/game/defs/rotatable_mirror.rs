@@ -0,0 +1,171 @@
+use avian2d::prelude::*;
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{
+    game::{Layers, LevelSystems},
+    shared::ResetLevels,
+};
+
+pub struct RotatableMirrorPlugin;
+
+impl Plugin for RotatableMirrorPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_entity::<RotatableMirrorBundle>("RotatableMirror");
+        app.add_observer(hydrate_rotatable_mirror);
+        app.add_observer(reset_rotatable_mirrors);
+        app.add_systems(
+            Update,
+            rotate_mirrors
+                .run_if(input_just_pressed(KeyCode::KeyF))
+                .in_set(LevelSystems::Input),
+        );
+    }
+}
+
+/// The default angle of a mirror and the default step it turns by, in degrees.
+const MIRROR_DEFAULT_ANGLE: f32 = 45.0;
+const MIRROR_DEFAULT_STEP: f32 = 45.0;
+/// How close Lyra has to be to a mirror to rotate it.
+const MIRROR_REACH: f32 = 16.0;
+const MIRROR_THICKNESS: f32 = 2.0;
+
+/// [`Component`] for a mirror that Lyra can rotate. The reflective surface is a child
+/// [`MirrorSurface`] with an oriented collider, so beams reflect off its true normal.
+#[derive(Component, Debug)]
+pub struct RotatableMirror {
+    /// Angle of the surface from the x axis, in radians
+    pub angle: f32,
+    init_angle: f32,
+    /// Angle the mirror turns by each time Lyra rotates it, in radians
+    pub step: f32,
+    pub length: f32,
+    /// The number of Lyra's colliders currently in reach of the mirror
+    in_reach: usize,
+}
+
+impl From<&EntityInstance> for RotatableMirror {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        let angle = entity_instance
+            .get_float_field("angle")
+            .copied()
+            .unwrap_or(MIRROR_DEFAULT_ANGLE)
+            .to_radians();
+        let step = entity_instance
+            .get_float_field("step")
+            .copied()
+            .unwrap_or(MIRROR_DEFAULT_STEP)
+            .to_radians();
+
+        RotatableMirror {
+            angle,
+            init_angle: angle,
+            step,
+            length: entity_instance.width as f32,
+            in_reach: 0,
+        }
+    }
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct RotatableMirrorBundle {
+    #[from_entity_instance]
+    mirror: RotatableMirror,
+}
+
+/// Marker [`Component`] for mirrors that aren't [`Mirror`](super::mirror::Mirror) tiles, like the
+/// surface of a [`RotatableMirror`] or a diagonal mirror.
+#[derive(Component, Default)]
+pub struct MirrorSurface;
+
+pub fn hydrate_rotatable_mirror(
+    event: On<Add, RotatableMirror>,
+    mut commands: Commands,
+    q_mirrors: Query<&RotatableMirror>,
+) {
+    let Ok(mirror) = q_mirrors.get(event.entity) else {
+        return;
+    };
+
+    commands
+        .entity(event.entity)
+        .insert(Collider::circle(MIRROR_REACH))
+        .insert(Sensor)
+        .insert(CollisionLayers::new(
+            Layers::SensorBox,
+            Layers::PlayerHurtbox,
+        ))
+        .with_child((
+            MirrorSurface,
+            Collider::rectangle(mirror.length, MIRROR_THICKNESS),
+            Friction::new(0.),
+            CollisionLayers::new(
+                Layers::Terrain,
+                [
+                    Layers::LightRay,
+                    Layers::PlayerCollider,
+                    Layers::BlueRay,
+                    Layers::WhiteRay,
+                ],
+            ),
+            Sprite::from_color(
+                Color::srgb(0.85, 0.9, 1.0),
+                Vec2::new(mirror.length, MIRROR_THICKNESS),
+            ),
+            Transform::from_rotation(Quat::from_rotation_z(mirror.angle)),
+        ));
+}
+
+fn update_mirror_surface(
+    mirror: &RotatableMirror,
+    children: &Children,
+    q_surfaces: &mut Query<&mut Transform, With<MirrorSurface>>,
+) {
+    let mut surfaces = q_surfaces.iter_many_mut(children);
+    while let Some(mut transform) = surfaces.fetch_next() {
+        transform.rotation = Quat::from_rotation_z(mirror.angle);
+    }
+}
+
+pub fn on_player_enter_mirror_reach(
+    event: On<CollisionStart>,
+    mut q_mirrors: Query<&mut RotatableMirror>,
+) {
+    if let Ok(mut mirror) = q_mirrors.get_mut(event.collider2) {
+        mirror.in_reach += 1;
+    }
+}
+
+pub fn on_player_leave_mirror_reach(
+    event: On<CollisionEnd>,
+    mut q_mirrors: Query<&mut RotatableMirror>,
+) {
+    if let Ok(mut mirror) = q_mirrors.get_mut(event.collider2) {
+        mirror.in_reach = mirror.in_reach.saturating_sub(1);
+    }
+}
+
+/// [`System`] that rotates the mirrors in Lyra's reach by one step.
+pub fn rotate_mirrors(
+    mut q_mirrors: Query<(&mut RotatableMirror, &Children)>,
+    mut q_surfaces: Query<&mut Transform, With<MirrorSurface>>,
+) {
+    for (mut mirror, children) in q_mirrors.iter_mut() {
+        if mirror.in_reach == 0 {
+            continue;
+        }
+        mirror.angle = (mirror.angle + mirror.step).rem_euclid(std::f32::consts::TAU);
+        update_mirror_surface(&mirror, children, &mut q_surfaces);
+    }
+}
+
+pub fn reset_rotatable_mirrors(
+    _: On<ResetLevels>,
+    mut q_mirrors: Query<(&mut RotatableMirror, &Children)>,
+    mut q_surfaces: Query<&mut Transform, With<MirrorSurface>>,
+) {
+    for (mut mirror, children) in q_mirrors.iter_mut() {
+        mirror.angle = mirror.init_angle;
+        update_mirror_surface(&mirror, children, &mut q_surfaces);
+    }
+}
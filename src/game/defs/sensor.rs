@@ -6,8 +6,14 @@ use enum_map::EnumMap;
 use crate::{
     asset::LoadResource,
     game::{
-        defs::crystal::{CrystalColor, CrystalToggleEvent},
-        light::{segments::simulate_light_sources, HitByLight, LightColor},
+        defs::{
+            crystal::{CrystalColor, CrystalToggleEvent},
+            signal::{signal_id_field, SetLevelSignal},
+        },
+        light::{
+            palette::LightPalette, segments::simulate_light_sources, HitByLight, LightColor,
+            LightHits,
+        },
         lighting::LineLight2d,
         particle::spark::SparkExplosionEvent,
         Layers, LevelSystems,
@@ -56,6 +62,31 @@ impl FromWorld for SensorAssets {
     }
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SensorMode {
+    /// Active while charged, and turns off once it drains
+    #[default]
+    Hold,
+    /// Stays active once charged, until a black beam drains it or the level resets
+    Latch,
+    /// Activates the first time it is charged, then locks until the level resets
+    OneShot,
+    /// Charges while no light hits it, so it is active while dark
+    Inverted,
+}
+
+impl From<&String> for SensorMode {
+    fn from(value: &String) -> Self {
+        match value.as_str() {
+            "Hold" => SensorMode::Hold,
+            "Latch" => SensorMode::Latch,
+            "OneShot" => SensorMode::OneShot,
+            "Inverted" => SensorMode::Inverted,
+            _ => panic!("String {} does not represent a SensorMode", value),
+        }
+    }
+}
+
 /// [`Component`] added to entities receptive to light. The
 /// [`activation_timer`](LightSensor::activation_timer) should be initialized in the
 /// `From<&EntityInstance>` implemenation for the [`LightSensorBundle`], if not default.
@@ -68,12 +99,19 @@ pub struct LightSensor {
     pub cumulative_exposure: Stopwatch,
     /// Stores the amount of light stored in the sensor, from 0 to 1.
     pub meter: f32,
-    /// Colors of light beams hitting the sensor
-    pub hit_by: EnumMap<LightColor, bool>,
+    /// Number of light beams of each color hitting the sensor
+    pub hit_by: LightHits,
+    /// The colors that need to hit the sensor for it to charge
+    pub requirement: SensorRequirement,
+    pub mode: SensorMode,
     /// Active state of the sensor
     pub is_active: bool,
+    /// If the sensor is a [`SensorMode::OneShot`] that has already activated
+    pub spent: bool,
     /// The color of the crystals to toggle
     pub toggle_color: CrystalColor,
+    /// The signal channel driven by the sensor. If set, crystals are not toggled.
+    pub event_id: Option<i32>,
     /// Meter's rate of change, per fixed timestep tick.
     rate: f32,
     /// Stored color used to animate the center of the sensor when the light no longer hits it
@@ -81,14 +119,24 @@ pub struct LightSensor {
 }
 
 impl LightSensor {
-    fn new(toggle_color: CrystalColor, millis: i32) -> Self {
+    fn new(
+        toggle_color: CrystalColor,
+        event_id: Option<i32>,
+        millis: i32,
+        requirement: SensorRequirement,
+        mode: SensorMode,
+    ) -> Self {
         let rate = 1.0 / (millis as f32) * (1000.0 / 64.0);
         LightSensor {
             meter: 0.0,
             cumulative_exposure: Stopwatch::default(),
-            hit_by: EnumMap::default(),
+            hit_by: LightHits::default(),
+            requirement,
+            mode,
             is_active: false,
+            spent: false,
             toggle_color,
+            event_id,
             rate,
             stored_color: Color::WHITE,
         }
@@ -96,19 +144,36 @@ impl LightSensor {
 
     fn reset(&mut self) {
         self.meter = 0.0;
-        self.hit_by = EnumMap::default();
+        self.hit_by = LightHits::default();
         self.is_active = false;
+        self.spent = false;
         self.cumulative_exposure.reset();
     }
 
+    /// If the light hitting the sensor meets its requirement.
     fn is_hit(&self) -> bool {
-        self.hit_by.iter().any(|(_, hit_by_color)| *hit_by_color)
+        self.requirement.is_met(&self.hit_by)
     }
 
-    fn iter_hit_color(&self) -> impl Iterator<Item = LightColor> + '_ {
-        self.hit_by
-            .iter()
-            .filter_map(|(color, hit_by_color)| if *hit_by_color { Some(color) } else { None })
+    /// If the sensor is charging, which depends on its [`SensorMode`].
+    fn is_charging(&self) -> bool {
+        match self.mode {
+            SensorMode::Hold => self.is_hit(),
+            // once latched, only black light drains the sensor
+            SensorMode::Latch if self.is_active => self.hit_by[LightColor::Black] == 0,
+            SensorMode::Latch => self.is_hit(),
+            SensorMode::OneShot => !self.spent && self.is_hit(),
+            SensorMode::Inverted => !self.is_hit(),
+        }
+    }
+
+    /// The color of the center of the sensor when it is empty.
+    fn empty_color(&self) -> Color {
+        match self.mode {
+            SensorMode::Inverted => Color::srgb(0.25, 0.25, 0.3),
+            SensorMode::OneShot if self.spent => Color::srgb(0.5, 0.5, 0.5),
+            _ => Color::WHITE,
+        }
     }
 }
 
@@ -123,7 +188,90 @@ impl From<&EntityInstance> for LightSensor {
             .get_int_field("activation_time")
             .expect("activation_time needs to be a float field on all sensors");
 
-        LightSensor::new(toggle_color, millis)
+        // NOTE: platform_id is the name of the field in older ldtk projects
+        let event_id = signal_id_field(entity_instance, &["event_id", "platform_id"]);
+
+        let mode = entity_instance
+            .get_enum_field("mode")
+            .map(SensorMode::from)
+            .unwrap_or_default();
+
+        LightSensor::new(
+            toggle_color,
+            event_id,
+            millis,
+            SensorRequirement::from(entity_instance),
+            mode,
+        )
+    }
+}
+
+/// Which colors of light a [`LightSensor`] needs to be hit by to charge, read from the optional
+/// `required_colors` (an array of light colors) and `require_all` (a bool) fields in LDtk.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SensorRequirement {
+    /// The colors that count, where none listed means that any color counts
+    pub colors: EnumMap<LightColor, bool>,
+    /// If every listed color needs to hit the sensor at once, instead of any one of them
+    pub all: bool,
+}
+
+impl SensorRequirement {
+    pub fn iter_colors(&self) -> impl Iterator<Item = LightColor> + '_ {
+        self.colors
+            .iter()
+            .filter_map(|(color, listed)| listed.then_some(color))
+    }
+
+    /// If a sensor hit by the given number of beams of each color charges. Black light drains a
+    /// sensor, even while other colors hit it.
+    pub fn is_met(&self, hit_by: &LightHits) -> bool {
+        let mut listed = self.iter_colors().peekable();
+        if listed.peek().is_none() {
+            return hit_by.is_lit();
+        }
+        if hit_by[LightColor::Black] > 0 {
+            return false;
+        }
+        if self.all {
+            listed.all(|color| hit_by[color] > 0)
+        } else {
+            listed.any(|color| hit_by[color] > 0)
+        }
+    }
+
+    /// The color that shows the requirement, which mixes the indicator colors of the listed
+    /// colors, or [`None`] if any color counts.
+    pub fn indicator_color(&self, palette: &LightPalette) -> Option<Color> {
+        let colors: Vec<Vec3> = self
+            .iter_colors()
+            .map(|color| palette[color].indicator_color().to_linear().to_vec3())
+            .collect();
+        if colors.is_empty() {
+            return None;
+        }
+        let mixed = colors.iter().sum::<Vec3>() / colors.len() as f32;
+        Some(LinearRgba::rgb(mixed.x, mixed.y, mixed.z).into())
+    }
+}
+
+impl From<&EntityInstance> for SensorRequirement {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        let mut colors: EnumMap<LightColor, bool> = EnumMap::default();
+        if let Ok(listed) = entity_instance.get_maybe_enums_field("required_colors") {
+            for color in listed.iter().flatten() {
+                colors[LightColor::from(color)] = true;
+            }
+        }
+        // black light only ever drains sensors
+        colors[LightColor::Black] = false;
+
+        SensorRequirement {
+            colors,
+            all: entity_instance
+                .get_bool_field("require_all")
+                .is_ok_and(|all| *all),
+        }
     }
 }
 
@@ -145,12 +293,13 @@ pub fn hydrate_sensor(
     mut commands: Commands,
     q_sensors: Query<&LightSensor>,
     sensor_assets: Res<SensorAssets>,
+    palette: Res<LightPalette>,
 ) {
     if q_sensors.is_empty() {
         return;
     }
 
-    let inner_sprite = Sprite::from_image(sensor_assets.sensor_inner.clone());
+    let mut inner_sprite = Sprite::from_image(sensor_assets.sensor_inner.clone());
     let mut outer_sprite = Sprite::from_image(sensor_assets.sensor_outer.clone());
     let center_sprite = Sprite::from_image(sensor_assets.sensor_center.clone());
 
@@ -160,10 +309,28 @@ pub fn hydrate_sensor(
 
     outer_sprite.color = ButtonColor::from(sensor.toggle_color);
 
+    // sensors that only some colors charge show them on their inner ring and in their light
+    let requirement_color = sensor.requirement.indicator_color(&palette);
+    if let Some(color) = requirement_color {
+        inner_sprite.color = color;
+    }
+    // sensors that need several colors at once get a pip for each of them
+    let mut pips: Vec<LightColor> = sensor.requirement.iter_colors().collect();
+    if !sensor.requirement.all || pips.len() < 2 {
+        pips.clear();
+    }
+    let light_color = requirement_color.unwrap_or(outer_sprite.color);
+    // latches get a bar under them, and one shots a dot
+    let mode_mark = match sensor.mode {
+        SensorMode::Latch => Some(Vec2::new(6., 1.)),
+        SensorMode::OneShot => Some(Vec2::new(2., 1.)),
+        SensorMode::Hold | SensorMode::Inverted => None,
+    };
+
     commands
         .entity(event.entity)
         .insert(LineLight2d::point(
-            outer_sprite.color.to_linear().to_vec3().extend(0.5),
+            light_color.to_linear().to_vec3().extend(0.5),
             35.0,
             0.02,
         ))
@@ -177,13 +344,26 @@ pub fn hydrate_sensor(
         .with_children(|sensor| {
             sensor.spawn(inner_sprite.clone());
             sensor.spawn(outer_sprite.clone());
+            for (i, color) in pips.iter().enumerate() {
+                let angle = std::f32::consts::TAU * i as f32 / pips.len() as f32;
+                sensor.spawn((
+                    Sprite::from_color(palette[*color].indicator_color(), Vec2::splat(2.)),
+                    Transform::from_translation((Vec2::from_angle(angle) * 6.).extend(0.1)),
+                ));
+            }
+            if let Some(size) = mode_mark {
+                sensor.spawn((
+                    Sprite::from_color(outer_sprite.color, size),
+                    Transform::from_xyz(0., -6., 0.1),
+                ));
+            }
         })
         .observe(
             |event: On<HitByLight>, mut q_sensors: Query<&mut LightSensor>| {
                 let Ok(mut sensor) = q_sensors.get_mut(event.entity) else {
                     return;
                 };
-                sensor.hit_by[event.color] = event.hit;
+                sensor.hit_by.apply(&event);
             },
         );
 }
@@ -204,31 +384,40 @@ pub fn update_light_sensors(
     mut commands: Commands,
     mut q_sensors: Query<(Entity, &mut LightSensor, &mut Sprite, &GlobalTransform)>,
     asset_server: Res<AssetServer>,
+    palette: Res<LightPalette>,
     time: Res<Time>,
     mut ev_spark_explosion: MessageWriter<SparkExplosionEvent>,
 ) {
     for (entity, mut sensor, mut sprite, transform) in q_sensors.iter_mut() {
-        let was_hit = sensor.is_hit();
+        let charging = sensor.is_charging();
 
-        if was_hit {
+        if charging {
             sensor.cumulative_exposure.tick(time.delta());
-
+        }
+        if sensor.is_hit() {
             // if the sensor was hit, update the stored color for the sensor
             let mut col = Vec3::ZERO;
-            for color in sensor.iter_hit_color() {
-                col += color.lighting_color() * 0.5;
+            for color in sensor.hit_by.iter_colors() {
+                col += palette[color].lighting_color() * 0.5;
             }
             col += Vec3::splat(0.6);
             sensor.stored_color = Color::srgb(col.x, col.y, col.z);
         }
 
-        let juice = if was_hit { sensor.rate } else { -sensor.rate };
+        let juice = if charging { sensor.rate } else { -sensor.rate };
         sensor.meter += juice;
 
-        let mut send_toggle = || {
-            commands.trigger(CrystalToggleEvent {
-                color: sensor.toggle_color,
-            });
+        let mut send_toggle = |active: bool| {
+            match sensor.event_id {
+                Some(id) => commands.trigger(SetLevelSignal {
+                    source: entity,
+                    id,
+                    active,
+                }),
+                None => commands.trigger(CrystalToggleEvent {
+                    color: sensor.toggle_color,
+                }),
+            }
             commands.entity(entity).with_child((
                 AudioPlayer::new(asset_server.load("sfx/button.wav")),
                 PlaybackSettings::DESPAWN,
@@ -240,19 +429,85 @@ pub fn update_light_sensors(
         };
 
         if sensor.meter > 1.0 {
-            if !sensor.is_active {
-                send_toggle();
+            if !sensor.is_active && !sensor.spent {
+                send_toggle(true);
                 sensor.is_active = true;
+                sensor.spent = sensor.mode == SensorMode::OneShot;
             }
             sensor.meter = 1.0;
         } else if sensor.meter < 0.0 {
-            if sensor.is_active {
-                send_toggle();
+            // one shots stay active after they fire, even as they drain
+            if sensor.is_active && sensor.mode != SensorMode::OneShot {
+                send_toggle(false);
                 sensor.is_active = false;
             }
             sensor.meter = 0.0;
         }
 
-        sprite.color = Color::WHITE.mix(&sensor.stored_color, sensor.meter);
+        sprite.color = sensor.empty_color().mix(&sensor.stored_color, sensor.meter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use enum_map::enum_map;
+
+    use super::*;
+
+    #[test]
+    fn sensor_requirements_need_listed_colors() {
+        let green_and_purple = enum_map! {
+            LightColor::Green | LightColor::Purple => true,
+            _ => false,
+        };
+        let mut hit_by = LightHits::default();
+        hit_by[LightColor::Green] = 1;
+
+        assert!(SensorRequirement::default().is_met(&hit_by));
+        let any = SensorRequirement {
+            colors: green_and_purple,
+            all: false,
+        };
+        assert!(any.is_met(&hit_by));
+        let all = SensorRequirement {
+            colors: green_and_purple,
+            all: true,
+        };
+        assert!(!all.is_met(&hit_by));
+
+        hit_by[LightColor::Purple] = 1;
+        assert!(all.is_met(&hit_by));
+        hit_by[LightColor::Black] = 1;
+        assert!(!all.is_met(&hit_by));
+    }
+
+    #[test]
+    fn sensor_modes_change_when_sensors_charge() {
+        let sensor = |mode| {
+            LightSensor::new(
+                CrystalColor::Pink,
+                None,
+                1000,
+                SensorRequirement::default(),
+                mode,
+            )
+        };
+
+        let mut inverted = sensor(SensorMode::Inverted);
+        assert!(inverted.is_charging());
+        inverted.hit_by[LightColor::Green] = 1;
+        assert!(!inverted.is_charging());
+
+        let mut latch = sensor(SensorMode::Latch);
+        latch.is_active = true;
+        assert!(latch.is_charging());
+        latch.hit_by[LightColor::Black] = 1;
+        assert!(!latch.is_charging());
+
+        let mut one_shot = sensor(SensorMode::OneShot);
+        one_shot.hit_by[LightColor::Green] = 1;
+        assert!(one_shot.is_charging());
+        one_shot.spent = true;
+        assert!(!one_shot.is_charging());
     }
 }
@@ -12,7 +12,7 @@ use crate::{
         animation::AnimationConfig,
         camera_op::{camera_position_from_level, camera_position_from_level_with_scale},
         dialogue::{Dialogue, DialogueAssets, DialogueEntry},
-        light::LightColor,
+        light::{palette::LightPalette, LightColor},
         lighting::LineLight2d,
         lyra::{
             beam::{BeamAction, PlayerLightInventory, PlayerLightProgress},
@@ -89,13 +89,14 @@ pub fn on_add_crystal_shard(
     shard_assets: Res<ShardAssets>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
     inventory: Single<&PlayerLightInventory, With<Lyra>>,
+    palette: Res<LightPalette>,
 ) {
     let Ok(shard) = q_crystal_shard.get(event.entity) else {
         return;
     };
 
     const CRYSTAL_SHARD_FRAMES: usize = 7;
-    const CRYSTAL_SHARD_ROWS: usize = 4;
+    const CRYSTAL_SHARD_ROWS: usize = 5;
 
     let texture_atlas_layout = texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
         UVec2::new(12, 16),
@@ -105,25 +106,17 @@ pub fn on_add_crystal_shard(
         None,
     ));
 
-    let shard_row = |shard: &CrystalShard| match shard.light_color {
-        LightColor::Blue => 0,
-        LightColor::Green => 1,
-        LightColor::Purple => 2,
-        LightColor::White => 3,
-        // LightColor::Black => 4,
-    };
-
     let visibility = if inventory.allowed[shard.light_color] {
         Visibility::Hidden
     } else {
         Visibility::Visible
     };
 
-    let start_index = shard_row(shard) * CRYSTAL_SHARD_FRAMES;
+    let start_index = palette[shard.light_color].shard_row * CRYSTAL_SHARD_FRAMES;
     commands
         .entity(event.entity)
         .insert(LineLight2d::point(
-            shard.light_color.lighting_color().extend(1.0),
+            palette[shard.light_color].lighting_color().extend(1.0),
             40.0,
             0.025,
         ))
@@ -254,19 +247,13 @@ pub fn on_shard_zoom_in_finished(
     mut commands: Commands,
     dialogue_assets: Res<DialogueAssets>,
     animation_res: Res<ShardAnimationRes>,
+    palette: Res<LightPalette>,
 ) {
     let on_dialogue_finish = commands.spawn(()).observe(on_shard_text_read_finish).id();
 
-    let text = match animation_res.color.unwrap() {
-        LightColor::Green => "Oh good, the first piece of the Divine Prism. This should let me shoot a bouncing light beam.",
-        LightColor::Blue => "Blue light, formerly known as the light of harmony. Could this one shoot through the active blue crystals above me?",
-        LightColor::White => "A different feeling than before... could this color have a special reflective properties?",
-        LightColor::Purple => "This one's even more powerful... the purple light beam should bounce twice instead of once.",
-    };
-
     commands.trigger(Dialogue {
         entries: vec![DialogueEntry {
-            text: text.to_string(),
+            text: palette[animation_res.color.unwrap()].unlock_text.clone(),
             image: dialogue_assets.lyra_happy.clone(),
         }],
         duration: Duration::from_millis(20),
@@ -0,0 +1,260 @@
+use bevy::{ecs::entity::EntityHashSet, platform::collections::HashMap, prelude::*};
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{
+    game::{defs::sensor::update_light_sensors, LevelSystems},
+    ldtk::EntityLevelParam,
+    shared::ResetLevels,
+};
+
+/// [`Plugin`] for the level signal bus. Triggers (sensors, buttons, ...) drive numbered channels
+/// with [`SetLevelSignal`], and targets (crystals, platforms, emitters, ...) observe
+/// [`LevelSignalChanged`] for the channels they are subscribed to.
+pub struct LevelSignalPlugin;
+
+impl Plugin for LevelSignalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelSignals>();
+        app.register_ldtk_entity::<LogicGateBundle>("LogicGate");
+        app.add_observer(on_set_level_signal);
+        app.add_observer(settle_added_logic_gate);
+        app.add_observer(reset_level_signals);
+        app.add_systems(
+            FixedUpdate,
+            update_logic_gates
+                .after(update_light_sensors)
+                .in_set(LevelSystems::Simulation),
+        );
+    }
+}
+
+/// Reads a channel id from the first of the `fields` on the entity that holds one. Negative ids
+/// are how LDtk marks an unconnected field, so they are treated as missing.
+pub fn signal_id_field(entity_instance: &EntityInstance, fields: &[&str]) -> Option<i32> {
+    fields
+        .iter()
+        .filter_map(|field| entity_instance.get_int_field(field).ok())
+        .copied()
+        .find(|id| *id >= 0)
+}
+
+/// Event triggered by a source to set its contribution to a channel. A channel is active while
+/// any of its sources are active.
+#[derive(Event)]
+pub struct SetLevelSignal {
+    pub source: Entity,
+    pub id: i32,
+    pub active: bool,
+}
+
+/// Event triggered when a channel of `level` turns on or off.
+#[derive(Event, Clone)]
+pub struct LevelSignalChanged {
+    pub level: LevelIid,
+    pub id: i32,
+    pub active: bool,
+}
+
+/// Stores the active sources of each channel. Each level has its own channels, so that levels can
+/// reuse ids, and they are cleared on [`ResetLevels`].
+#[derive(Resource, Default, Debug)]
+pub struct LevelSignals {
+    active: HashMap<(LevelIid, i32), EntityHashSet>,
+}
+
+impl LevelSignals {
+    pub fn is_active(&self, level: &LevelIid, id: i32) -> bool {
+        self.active
+            .get(&(level.clone(), id))
+            .is_some_and(|sources| !sources.is_empty())
+    }
+
+    /// Sets the contribution of `source` to a channel, returning true if the channel changed.
+    fn set(&mut self, source: Entity, level: &LevelIid, id: i32, active: bool) -> bool {
+        let was_active = self.is_active(level, id);
+        let sources = self.active.entry((level.clone(), id)).or_default();
+        if active {
+            sources.insert(source);
+        } else {
+            sources.remove(&source);
+        }
+        was_active != self.is_active(level, id)
+    }
+}
+
+pub fn on_set_level_signal(
+    event: On<SetLevelSignal>,
+    mut commands: Commands,
+    mut signals: ResMut<LevelSignals>,
+    levels: EntityLevelParam,
+) {
+    let Some(level) = levels.level_of(event.source) else {
+        return;
+    };
+    if signals.set(event.source, level, event.id, event.active) {
+        commands.trigger(LevelSignalChanged {
+            level: level.clone(),
+            id: event.id,
+            active: event.active,
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogicGateKind {
+    /// Active while all inputs are active
+    And,
+    /// Active while any input is active
+    Or,
+    /// Active while no inputs are active
+    Not,
+    /// Turns on once any input is active, and stays on until the level resets
+    Latch,
+}
+
+impl From<&String> for LogicGateKind {
+    fn from(value: &String) -> Self {
+        match value.as_str() {
+            "And" => LogicGateKind::And,
+            "Or" => LogicGateKind::Or,
+            "Not" => LogicGateKind::Not,
+            "Latch" => LogicGateKind::Latch,
+            _ => panic!("String {} does not represent a LogicGateKind", value),
+        }
+    }
+}
+
+/// [`Component`] for a logic node placed in LDtk. It reads the `inputs` channels of its level and
+/// drives the `event_id` channel with the result.
+#[derive(Component, Debug)]
+pub struct LogicGate {
+    pub kind: LogicGateKind,
+    pub inputs: Vec<i32>,
+    pub event_id: Option<i32>,
+    output: bool,
+}
+
+impl LogicGate {
+    fn evaluate(&self, level: &LevelIid, signals: &LevelSignals) -> bool {
+        let mut inputs = self.inputs.iter().map(|id| signals.is_active(level, *id));
+        match self.kind {
+            LogicGateKind::And => !self.inputs.is_empty() && inputs.all(|active| active),
+            LogicGateKind::Or => inputs.any(|active| active),
+            LogicGateKind::Not => !inputs.any(|active| active),
+            LogicGateKind::Latch => self.output || inputs.any(|active| active),
+        }
+    }
+}
+
+impl From<&EntityInstance> for LogicGate {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        let kind: LogicGateKind = entity_instance
+            .get_enum_field("gate")
+            .expect("gate needs to be an enum field on all logic gates")
+            .into();
+
+        let inputs = entity_instance
+            .get_maybe_ints_field("inputs")
+            .expect("inputs needs to be an int array field on all logic gates")
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|id| *id >= 0)
+            .collect();
+
+        let event_id = signal_id_field(entity_instance, &["event_id"]);
+
+        LogicGate {
+            kind,
+            inputs,
+            event_id,
+            output: false,
+        }
+    }
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct LogicGateBundle {
+    #[from_entity_instance]
+    gate: LogicGate,
+}
+
+/// Brings all gates to a stable state without notifying targets, so that the resting state of a
+/// level (e.g. the output of a [`LogicGateKind::Not`]) doesn't count as a change.
+fn settle_logic_gates(
+    q_gates: &mut Query<(Entity, &mut LogicGate)>,
+    levels: &EntityLevelParam,
+    signals: &mut LevelSignals,
+    reset: bool,
+) {
+    if reset {
+        for (_, mut gate) in q_gates.iter_mut() {
+            gate.output = false;
+        }
+    }
+    for _ in 0..=q_gates.iter().count() {
+        let mut changed = false;
+        for (entity, mut gate) in q_gates.iter_mut() {
+            let Some(level) = levels.level_of(entity) else {
+                continue;
+            };
+            let output = gate.evaluate(level, signals);
+            if output != gate.output {
+                gate.output = output;
+                if let Some(id) = gate.event_id {
+                    signals.set(entity, level, id, output);
+                }
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+}
+
+pub fn settle_added_logic_gate(
+    _: On<Add, LogicGate>,
+    mut q_gates: Query<(Entity, &mut LogicGate)>,
+    levels: EntityLevelParam,
+    mut signals: ResMut<LevelSignals>,
+) {
+    settle_logic_gates(&mut q_gates, &levels, &mut signals, false);
+}
+
+pub fn reset_level_signals(
+    _: On<ResetLevels>,
+    mut q_gates: Query<(Entity, &mut LogicGate)>,
+    levels: EntityLevelParam,
+    mut signals: ResMut<LevelSignals>,
+) {
+    signals.active.clear();
+    settle_logic_gates(&mut q_gates, &levels, &mut signals, true);
+}
+
+/// [`System`] that re-evaluates every [`LogicGate`] once per tick. Gates that feed into each other
+/// propagate one step per tick, so accidental loops oscillate instead of hanging the game.
+pub fn update_logic_gates(
+    mut commands: Commands,
+    mut q_gates: Query<(Entity, &mut LogicGate)>,
+    levels: EntityLevelParam,
+    signals: Res<LevelSignals>,
+) {
+    for (entity, mut gate) in q_gates.iter_mut() {
+        let Some(level) = levels.level_of(entity) else {
+            continue;
+        };
+        let output = gate.evaluate(level, &signals);
+        if output == gate.output {
+            continue;
+        }
+        gate.output = output;
+        if let Some(id) = gate.event_id {
+            commands.trigger(SetLevelSignal {
+                source: entity,
+                id,
+                active: output,
+            });
+        }
+    }
+}
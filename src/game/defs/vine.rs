@@ -0,0 +1,294 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{
+    game::{
+        defs::one_way_platform::OneWayPlatform,
+        light::{
+            palette::LightPalette, segments::simulate_light_sources, HitByLight, LightColor,
+            LightHits,
+        },
+        lyra::strand::{update_strand, Strand},
+        Layers, LevelSystems,
+    },
+    shared::ResetLevels,
+};
+
+pub struct VinePlugin;
+
+impl Plugin for VinePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_entity::<VineBundle>("Vine");
+        app.add_observer(hydrate_vine);
+        app.add_observer(reset_vines);
+        app.add_systems(
+            FixedUpdate,
+            grow_vines
+                .after(simulate_light_sources)
+                .before(update_strand)
+                .in_set(LevelSystems::Simulation),
+        );
+    }
+}
+
+/// The length of each piece of a vine, which are spawned and despawned one at a time.
+const VINE_PIECE_LENGTH: f32 = 8.;
+const VINE_THICKNESS: f32 = 3.;
+/// How fast a lit vine grows, in pixels per second.
+const VINE_GROW_SPEED: f32 = 48.;
+/// How fast a vine withers once it is no longer lit, in pixels per second.
+const VINE_WITHER_SPEED: f32 = 12.;
+/// The number of [`Strand`]s that dangle from the tip of a vine.
+const VINE_TIP_STRANDS: usize = 3;
+const VINE_COLOR: Color = Color::srgb(0.3, 0.6, 0.25);
+
+/// [`Component`] for a plant whose vine grows along its `path` while a beam of its color hits its
+/// bulb, and withers once the light is gone. Each [`VINE_PIECE_LENGTH`] of the vine is a
+/// [`OneWayPlatform`] that Lyra can stand on, and a few [`Strand`]s dangle from its tip.
+#[derive(Component, Debug)]
+pub struct Vine {
+    pub color: LightColor,
+    /// The pieces of the vine from the bulb to the end of the path as `(start, end)`, relative to
+    /// the bulb
+    pieces: Vec<(Vec2, Vec2)>,
+    /// Number of beams of each color hitting the bulb
+    hit_by: LightHits,
+    /// How far along its path the vine has grown, in pixels
+    growth: f32,
+    /// The spawned pieces, in order from the bulb
+    spawned: Vec<Entity>,
+    /// The strands dangling from the tip, if the vine has grown at all
+    tip: Vec<Entity>,
+}
+
+impl Vine {
+    fn new(color: LightColor, path: &[Vec2]) -> Self {
+        let mut pieces = Vec::new();
+        for pair in path.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            let count = (from.distance(to) / VINE_PIECE_LENGTH).ceil().max(1.) as usize;
+            for i in 0..count {
+                pieces.push((
+                    from.lerp(to, i as f32 / count as f32),
+                    from.lerp(to, (i + 1) as f32 / count as f32),
+                ));
+            }
+        }
+        Vine {
+            color,
+            pieces,
+            hit_by: LightHits::default(),
+            growth: 0.,
+            spawned: Vec::new(),
+            tip: Vec::new(),
+        }
+    }
+
+    fn length(&self) -> f32 {
+        self.pieces
+            .iter()
+            .map(|(start, end)| start.distance(*end))
+            .sum()
+    }
+
+    /// Grows or withers the vine over `dt` seconds, and returns the number of pieces that have
+    /// fully grown.
+    fn grow(&mut self, dt: f32) -> usize {
+        self.growth = if self.hit_by[self.color] > 0 {
+            (self.growth + VINE_GROW_SPEED * dt).min(self.length())
+        } else {
+            (self.growth - VINE_WITHER_SPEED * dt).max(0.)
+        };
+
+        let mut grown = 0.;
+        self.pieces
+            .iter()
+            .take_while(|(start, end)| {
+                grown += start.distance(*end);
+                grown <= self.growth + 0.01
+            })
+            .count()
+    }
+}
+
+#[derive(Bundle)]
+pub struct VineBundle {
+    vine: Vine,
+}
+
+impl LdtkEntity for VineBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        layer_instance: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlasLayout>,
+    ) -> Self {
+        let grid_size = layer_instance.grid_size as f32;
+        let origin = entity_instance.grid;
+
+        // NOTE: path points are in ldtk grid coordinates, so flip the y value
+        let path: Vec<Vec2> = std::iter::once(Vec2::ZERO)
+            .chain(
+                entity_instance
+                    .get_maybe_points_field("path")
+                    .expect("path needs to be a points field on all vines")
+                    .iter()
+                    .flatten()
+                    .map(|point| {
+                        Vec2::new(
+                            (point.x - origin.x) as f32 * grid_size,
+                            -(point.y - origin.y) as f32 * grid_size,
+                        )
+                    }),
+            )
+            .collect();
+
+        let color = entity_instance
+            .get_enum_field("light_color")
+            .map(LightColor::from)
+            .unwrap_or(LightColor::Green);
+
+        Self {
+            vine: Vine::new(color, &path),
+        }
+    }
+}
+
+pub fn hydrate_vine(
+    event: On<Add, Vine>,
+    mut commands: Commands,
+    q_vines: Query<&Vine>,
+    palette: Res<LightPalette>,
+) {
+    let Ok(vine) = q_vines.get(event.entity) else {
+        return;
+    };
+
+    commands
+        .entity(event.entity)
+        .insert(Collider::circle(4.))
+        .insert(CollisionLayers::new(
+            Layers::LightSensor,
+            [Layers::LightRay, Layers::WhiteRay, Layers::BlueRay],
+        ))
+        .insert(Sprite::from_color(
+            palette[vine.color].indicator_color(),
+            Vec2::splat(6.),
+        ))
+        .observe(|event: On<HitByLight>, mut q_vines: Query<&mut Vine>| {
+            let Ok(mut vine) = q_vines.get_mut(event.entity) else {
+                return;
+            };
+            vine.hit_by.apply(&event);
+        });
+}
+
+fn spawn_vine_tip(commands: &mut Commands, vine_entity: Entity, piece: Entity) -> Vec<Entity> {
+    let mut connect = piece;
+    (0..VINE_TIP_STRANDS)
+        .map(|i| {
+            connect = commands
+                .spawn((
+                    Strand::new(connect, Vec2::ZERO, 3., 0.1, 0.8, i as u32),
+                    Sprite::from_color(VINE_COLOR, Vec2::splat(2.)),
+                    Transform::from_xyz(0., 0., -0.1),
+                    ChildOf(vine_entity),
+                ))
+                .id();
+            connect
+        })
+        .collect()
+}
+
+/// [`System`] that grows lit [`Vine`]s and withers the rest, spawning and despawning their pieces
+/// as they change length.
+pub fn grow_vines(
+    mut commands: Commands,
+    mut q_vines: Query<(Entity, &mut Vine)>,
+    mut q_strands: Query<&mut Strand>,
+    time: Res<Time>,
+) {
+    for (entity, mut vine) in q_vines.iter_mut() {
+        let grown = vine.grow(time.delta_secs());
+        if grown == vine.spawned.len() {
+            continue;
+        }
+
+        while vine.spawned.len() > grown {
+            let piece = vine
+                .spawned
+                .pop()
+                .expect("vine should have a piece to wither");
+            commands.entity(piece).despawn();
+        }
+        while vine.spawned.len() < grown {
+            let (start, end) = vine.pieces[vine.spawned.len()];
+            let length = start.distance(end);
+            let piece = commands
+                .spawn((
+                    Collider::rectangle(length, VINE_THICKNESS),
+                    CollisionLayers::new(Layers::LightBridge, Layers::PlayerCollider),
+                    OneWayPlatform::default(),
+                    ActiveCollisionHooks::MODIFY_CONTACTS,
+                    Friction::new(0.),
+                    Sprite::from_color(VINE_COLOR, Vec2::new(length, VINE_THICKNESS)),
+                    Transform::from_translation(start.midpoint(end).extend(-0.1))
+                        .with_rotation(Quat::from_rotation_z((end - start).to_angle())),
+                    ChildOf(entity),
+                ))
+                .id();
+            vine.spawned.push(piece);
+        }
+
+        // the tip dangles from the end of the last piece
+        let Some(&last) = vine.spawned.last() else {
+            for strand in vine.tip.drain(..) {
+                commands.entity(strand).despawn();
+            }
+            continue;
+        };
+        if vine.tip.is_empty() {
+            vine.tip = spawn_vine_tip(&mut commands, entity, last);
+        }
+        let (start, end) = vine.pieces[vine.spawned.len() - 1];
+        if let Ok(mut root) = q_strands.get_mut(vine.tip[0]) {
+            root.connect = last;
+            root.offset = (end - start) / 2.;
+        }
+    }
+}
+
+pub fn reset_vines(_: On<ResetLevels>, mut commands: Commands, mut q_vines: Query<&mut Vine>) {
+    for mut vine in q_vines.iter_mut() {
+        let vine = &mut *vine;
+        vine.hit_by = LightHits::default();
+        vine.growth = 0.;
+        for entity in vine.spawned.drain(..).chain(vine.tip.drain(..)) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vine_grows_piece_by_piece() {
+        let mut vine = Vine::new(
+            LightColor::Green,
+            &[Vec2::ZERO, Vec2::new(16., 0.), Vec2::new(16., 8.)],
+        );
+        assert_eq!(vine.pieces.len(), 3);
+
+        vine.hit_by[LightColor::Green] = 1;
+        assert_eq!(vine.grow(VINE_PIECE_LENGTH / VINE_GROW_SPEED), 1);
+        assert_eq!(vine.grow(1.), 3);
+
+        vine.hit_by[LightColor::Green] = 0;
+        assert_eq!(vine.grow(VINE_PIECE_LENGTH / VINE_WITHER_SPEED), 2);
+    }
+}
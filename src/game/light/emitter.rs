@@ -0,0 +1,344 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{
+    asset::LoadResource,
+    game::{
+        defs::signal::{signal_id_field, LevelSignalChanged},
+        light::{
+            palette::LightPalette,
+            segments::{tick_light_sources, LightBeamSourceDespawn},
+            LightBeamSource, LightColor,
+        },
+        lighting::LineLight2d,
+        LevelSystems,
+    },
+    ldtk::EntityLevelParam,
+    shared::ResetLevels,
+};
+
+/// [`Plugin`] for light emitters placed in LDtk.
+pub struct LightEmitterPlugin;
+
+impl Plugin for LightEmitterPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<LightEmitterAssets>();
+        app.load_resource::<LightEmitterAssets>();
+        app.register_ldtk_entity::<LightEmitterBundle>("LightEmitter");
+        app.register_ldtk_entity::<HazardLaserBundle>("HazardLaser");
+        app.add_observer(hydrate_light_emitter);
+        app.add_observer(on_light_emitter_signal);
+        app.add_observer(reset_light_emitters);
+        app.add_systems(
+            Update,
+            (tick_hazard_lasers, update_light_emitters)
+                .chain()
+                .before(tick_light_sources)
+                .in_set(LevelSystems::Simulation),
+        );
+    }
+}
+
+#[derive(Resource, Asset, Reflect, Clone)]
+#[reflect(Resource)]
+pub struct LightEmitterAssets {
+    #[dependency]
+    compass: Handle<Image>,
+    #[dependency]
+    compass_gold: Handle<Image>,
+}
+
+impl FromWorld for LightEmitterAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+
+        Self {
+            compass: asset_server.load("light/compass.png"),
+            compass_gold: asset_server.load("light/compass-gold.png"),
+        }
+    }
+}
+
+/// [`Component`] for a fixed light source placed in a level. While active, the emitter keeps a
+/// [`LightBeamSource`] alive in each of its directions. The beams are children of the emitter
+/// marked with [`LightEmitterBeam`], and are not part of Lyra's inventory.
+#[derive(Component, Debug)]
+pub struct LightEmitter {
+    pub color: LightColor,
+    /// World position the beams start from
+    pub start_pos: Vec2,
+    /// Offset of `start_pos` from the center of the emitter
+    pub offset: Vec2,
+    pub start_dir: Dir2,
+    /// If the emitter also fires a beam in the opposite direction
+    pub both_directions: bool,
+    /// Signal channel that turns the emitter on or off whenever it changes
+    pub event_id: Option<i32>,
+    init_active: bool,
+    pub active: bool,
+}
+
+impl LightEmitter {
+    pub fn directions(&self) -> impl Iterator<Item = Dir2> {
+        let back = self.both_directions.then_some(-self.start_dir);
+        std::iter::once(self.start_dir).chain(back)
+    }
+}
+
+impl From<&EntityInstance> for LightEmitter {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        let color = entity_instance
+            .get_enum_field("light_color")
+            .map(LightColor::from)
+            .unwrap_or(LightColor::White);
+
+        let x_offset = entity_instance
+            .get_float_field("XOffset")
+            .copied()
+            .unwrap_or(0.);
+        let y_offset = entity_instance
+            .get_float_field("YOffset")
+            .copied()
+            .unwrap_or(0.);
+        let both_directions = entity_instance
+            .get_bool_field("BothDirections")
+            .is_ok_and(|val| *val);
+
+        // NOTE: the direction point is in ldtk grid coordinates, so flip the y value
+        let target = *entity_instance
+            .get_point_field("Direction")
+            .expect("Direction needs to be a point field on all light emitters");
+        let delta = target - entity_instance.grid;
+        let start_dir = Dir2::new(Vec2::new(delta.x as f32, -delta.y as f32))
+            .expect("Direction of a light emitter should not point at the emitter itself");
+
+        let (Some(x), Some(y)) = (entity_instance.world_x, entity_instance.world_y) else {
+            panic!("Light emitter entity has no coordinates! (This is probably because your LDTK world is not in free layout mode.)");
+        };
+        let size = Vec2::new(entity_instance.width as f32, entity_instance.height as f32);
+        let center_offset = (Vec2::splat(0.5) - entity_instance.pivot) * size;
+        let center = Vec2::new(x as f32 + center_offset.x, -(y as f32 + center_offset.y));
+        let offset = Vec2::new(x_offset, y_offset);
+
+        let event_id = signal_id_field(entity_instance, &["event_id"]);
+        let init_active = entity_instance
+            .get_bool_field("active")
+            .copied()
+            .unwrap_or(true);
+
+        LightEmitter {
+            color,
+            start_pos: center + offset,
+            offset,
+            start_dir,
+            both_directions,
+            event_id,
+            init_active,
+            active: init_active,
+        }
+    }
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct LightEmitterBundle {
+    #[from_entity_instance]
+    emitter: LightEmitter,
+}
+
+/// The color of the beams of [`HazardLaser`]s.
+pub const HAZARD_BEAM_COLOR: Color = Color::srgb(4.0, 0.3, 0.2);
+/// The color of the light given off by the beams of [`HazardLaser`]s.
+pub const HAZARD_LIGHTING_COLOR: Vec3 = Vec3::new(1.0, 0.1, 0.05);
+
+/// [`Component`] that turns a [`LightEmitter`] into a hazard, whose beams are marked with
+/// [`HazardBeam`] and kill Lyra. The beams still bounce like beams of the emitter's color, but
+/// don't light up sensors. If `off_time` is more than zero, the laser cycles between being on for
+/// `on_time` seconds and off for `off_time` seconds, starting `offset` seconds into the cycle.
+#[derive(Component, Debug)]
+pub struct HazardLaser {
+    pub on_time: f32,
+    pub off_time: f32,
+    pub offset: f32,
+    /// Seconds since the level was last reset
+    elapsed: f32,
+}
+
+impl HazardLaser {
+    pub fn is_on(&self) -> bool {
+        if self.off_time <= 0. {
+            return true;
+        }
+        (self.elapsed + self.offset).rem_euclid(self.on_time + self.off_time) < self.on_time
+    }
+}
+
+impl From<&EntityInstance> for HazardLaser {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        let field = |name| {
+            entity_instance
+                .get_float_field(name)
+                .copied()
+                .unwrap_or(0.)
+                .max(0.)
+        };
+        HazardLaser {
+            on_time: field("OnTime"),
+            off_time: field("OffTime"),
+            offset: field("CycleOffset"),
+            elapsed: 0.,
+        }
+    }
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct HazardLaserBundle {
+    #[from_entity_instance]
+    emitter: LightEmitter,
+    #[from_entity_instance]
+    laser: HazardLaser,
+}
+
+/// Marker [`Component`] for the [`LightBeamSource`]s spawned by a [`LightEmitter`].
+#[derive(Component, Default)]
+pub struct LightEmitterBeam;
+
+/// Marker [`Component`] for the [`LightBeamSource`]s spawned by a [`HazardLaser`]. Their segments
+/// are [`DangerBox`](crate::game::defs::DangerBox)es.
+#[derive(Component, Default)]
+pub struct HazardBeam;
+
+pub fn hydrate_light_emitter(
+    event: On<Add, LightEmitter>,
+    mut commands: Commands,
+    q_emitters: Query<(&LightEmitter, Has<HazardLaser>)>,
+    emitter_assets: Res<LightEmitterAssets>,
+    palette: Res<LightPalette>,
+) {
+    let Ok((emitter, hazard)) = q_emitters.get(event.entity) else {
+        return;
+    };
+    let (beam_color, lighting_color) = if hazard {
+        (HAZARD_BEAM_COLOR, HAZARD_LIGHTING_COLOR)
+    } else {
+        let def = &palette[emitter.color];
+        (def.light_beam_color(), def.lighting_color())
+    };
+
+    let sprite_transform = Transform::from_translation(emitter.offset.extend(0.))
+        .with_rotation(Quat::from_rotation_z(emitter.start_dir.to_angle()));
+
+    let mut compass_sprite = Sprite::from_image(emitter_assets.compass.clone());
+    compass_sprite.color = Color::srgb(2.0, 2.0, 2.0);
+    let mut outer_sprite = Sprite::from_image(emitter_assets.compass_gold.clone());
+    outer_sprite.color = beam_color.mix(&Color::BLACK, 0.4);
+
+    commands
+        .entity(event.entity)
+        .insert(LineLight2d::point(lighting_color.extend(1.0), 30.0, 0.02))
+        .with_children(|parent| {
+            parent.spawn((compass_sprite, sprite_transform));
+            parent.spawn((outer_sprite, sprite_transform));
+        });
+}
+
+pub fn on_light_emitter_signal(
+    event: On<LevelSignalChanged>,
+    mut q_emitters: Query<(Entity, &mut LightEmitter)>,
+    levels: EntityLevelParam,
+) {
+    for (entity, mut emitter) in q_emitters.iter_mut() {
+        if emitter.event_id == Some(event.id) && levels.level_of(entity) == Some(&event.level) {
+            emitter.active = !emitter.active;
+        }
+    }
+}
+
+pub fn reset_light_emitters(
+    _: On<ResetLevels>,
+    mut q_emitters: Query<(&mut LightEmitter, Option<&mut HazardLaser>)>,
+) {
+    for (mut emitter, laser) in q_emitters.iter_mut() {
+        emitter.active = emitter.init_active;
+        if let Some(mut laser) = laser {
+            laser.elapsed = 0.;
+        }
+    }
+}
+
+pub fn tick_hazard_lasers(mut q_lasers: Query<&mut HazardLaser>, time: Res<Time>) {
+    for mut laser in q_lasers.iter_mut() {
+        laser.elapsed += time.delta_secs();
+    }
+}
+
+/// [`System`] that makes sure active [`LightEmitter`]s have their beams, and retracts the beams of
+/// inactive ones. [`HazardLaser`]s are also inactive during the off part of their cycle. Beams are
+/// despawned with the rest of the light sources on [`ResetLevels`], after which they are spawned
+/// again here.
+pub fn update_light_emitters(
+    mut commands: Commands,
+    q_emitters: Query<(
+        Entity,
+        &LightEmitter,
+        Option<&HazardLaser>,
+        Option<&Children>,
+    )>,
+    q_beams: Query<Has<LightBeamSourceDespawn>, With<LightEmitterBeam>>,
+) {
+    for (entity, emitter, laser, children) in q_emitters.iter() {
+        let beams: Vec<(Entity, bool)> = children
+            .into_iter()
+            .flatten()
+            .filter_map(|child| {
+                q_beams
+                    .get(*child)
+                    .ok()
+                    .map(|despawning| (*child, despawning))
+            })
+            .collect();
+
+        if emitter.active && laser.is_none_or(HazardLaser::is_on) {
+            if beams.iter().any(|(_, despawning)| !despawning) {
+                continue;
+            }
+            for dir in emitter.directions() {
+                let mut beam =
+                    commands.spawn(LightBeamSource::new(emitter.start_pos, dir, emitter.color));
+                beam.insert(LightEmitterBeam).insert(ChildOf(entity));
+                if laser.is_some() {
+                    beam.insert(HazardBeam);
+                }
+            }
+        } else {
+            for (beam, despawning) in beams {
+                if !despawning {
+                    commands.entity(beam).insert(LightBeamSourceDespawn);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hazard_laser_follows_duty_cycle() {
+        let mut laser = HazardLaser {
+            on_time: 1.,
+            off_time: 2.,
+            offset: 0.5,
+            elapsed: 0.,
+        };
+        assert!(laser.is_on());
+        laser.elapsed = 0.75;
+        assert!(!laser.is_on());
+        laser.elapsed = 2.75;
+        assert!(laser.is_on());
+
+        laser.off_time = 0.;
+        laser.elapsed = 0.75;
+        assert!(laser.is_on());
+    }
+}
@@ -0,0 +1,260 @@
+use std::ops::Index;
+
+use avian2d::prelude::*;
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+use enum_map::{enum_map, EnumMap};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    config::Config,
+    game::{
+        light::{
+            render::{LightMaterial, LightRenderData},
+            LightColor,
+        },
+        Layers,
+    },
+};
+
+/// [`Plugin`] that loads the [`LightPalette`], and keeps the [`LightPalette`] [`Resource`] up to
+/// date with it.
+pub struct LightPalettePlugin;
+
+impl Plugin for LightPalettePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<LightPalette>();
+        app.init_asset_loader::<LightPaletteLoader>();
+        app.init_resource::<LightPalette>();
+
+        let path = app
+            .world()
+            .resource::<Config>()
+            .level_config
+            .palette_path
+            .clone();
+        let handle = app.world().resource::<AssetServer>().load(path);
+        app.insert_resource(LightPaletteHandle(handle));
+        app.add_systems(PreUpdate, apply_light_palette);
+    }
+}
+
+/// The palette built into the game, used until the palette asset is loaded and by code that runs
+/// without an [`App`].
+const DEFAULT_PALETTE: &str = include_str!("../../../assets/light/default.palette.toml");
+
+/// How a [`LightColor`] behaves and looks.
+#[derive(Deserialize, Debug, Clone)]
+pub struct LightColorDef {
+    /// The number of bounces off of terrain beams of this color can make
+    pub bounces: usize,
+    /// How far a beam of this color travels on full energy, in levels with a `BeamRange`
+    pub range: f32,
+    /// The fraction of its full energy a beam of this color loses each time it bounces, in levels
+    /// with a `BeamRange`
+    pub bounce_energy: f32,
+    /// The layer of this color's beams
+    pub layer: Layers,
+    /// The layers that beams of this color pass through, out of the layers beams can hit
+    #[serde(default)]
+    pub pass_through: Vec<Layers>,
+    /// The color of the beam itself
+    pub beam_color: [f32; 3],
+    /// The color of the light the beam gives off
+    pub lighting_color: [f32; 3],
+    /// The color used for this light in indicators and tiles
+    pub indicator_color: [f32; 3],
+    /// The key that switches to this color
+    pub key: KeyCode,
+    /// The row of this color in the crystal shard sprite sheet
+    pub shard_row: usize,
+    /// The index of this color's shard in the indicator icons that circle Lyra
+    pub indicator_icon: usize,
+    /// What Lyra says when she picks up this color's shard
+    pub unlock_text: String,
+}
+
+impl LightColorDef {
+    pub fn lighting_color(&self) -> Vec3 {
+        Vec3::from_array(self.lighting_color)
+    }
+
+    pub fn light_beam_color(&self) -> Color {
+        let [r, g, b] = self.beam_color;
+        Color::srgb(r, g, b)
+    }
+
+    pub fn indicator_color(&self) -> Color {
+        let [r, g, b] = self.indicator_color;
+        Color::srgb(r, g, b)
+    }
+
+    /// The [`CollisionLayers`] beams of this color are cast with.
+    pub fn collision_layers(&self) -> CollisionLayers {
+        let filters = self
+            .pass_through
+            .iter()
+            .fold(light_interaction_layers(), |mask, layer| {
+                LayerMask(mask.0 & !layer.to_bits())
+            });
+        CollisionLayers::new(self.layer.to_bits(), filters)
+    }
+}
+
+/// All the layers that a beam of some color can hit.
+pub fn light_interaction_layers() -> LayerMask {
+    LayerMask::from([
+        Layers::Terrain,
+        Layers::LightSensor,
+        Layers::WhiteRay,
+        Layers::BlackRay,
+        Layers::Spike,
+        Layers::BlueCrystal,
+        Layers::Platform,
+        Layers::Water,
+        Layers::Portal,
+    ])
+}
+
+/// The layout of the palette file, with a table for each color.
+#[derive(Deserialize)]
+struct LightPaletteFile {
+    green: LightColorDef,
+    purple: LightColorDef,
+    white: LightColorDef,
+    blue: LightColorDef,
+    black: LightColorDef,
+}
+
+/// [`Asset`] and [`Resource`] that defines how each [`LightColor`] behaves, loaded from a
+/// `.palette.toml` file so that it can be changed without touching the code. Index it with a
+/// [`LightColor`] to get its [`LightColorDef`].
+#[derive(Asset, Resource, TypePath, Deserialize, Debug, Clone)]
+#[serde(from = "LightPaletteFile")]
+pub struct LightPalette(EnumMap<LightColor, LightColorDef>);
+
+impl From<LightPaletteFile> for LightPalette {
+    fn from(file: LightPaletteFile) -> Self {
+        LightPalette(enum_map! {
+            LightColor::Green => file.green.clone(),
+            LightColor::Purple => file.purple.clone(),
+            LightColor::White => file.white.clone(),
+            LightColor::Blue => file.blue.clone(),
+            LightColor::Black => file.black.clone(),
+        })
+    }
+}
+
+impl Default for LightPalette {
+    fn default() -> Self {
+        toml::from_str(DEFAULT_PALETTE).expect("The built in light palette should be valid")
+    }
+}
+
+impl Index<LightColor> for LightPalette {
+    type Output = LightColorDef;
+
+    fn index(&self, color: LightColor) -> &Self::Output {
+        &self.0[color]
+    }
+}
+
+impl LightPalette {
+    /// Iterates over the colors in the order they are switched through.
+    pub fn iter(&self) -> impl Iterator<Item = (LightColor, &LightColorDef)> {
+        self.0.iter()
+    }
+}
+
+#[derive(Resource)]
+struct LightPaletteHandle(Handle<LightPalette>);
+
+#[derive(Debug, Error)]
+pub enum LightPaletteError {
+    #[error("Could not read light palette: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Light palette is not valid UTF-8: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+    #[error("Could not parse light palette: {0}")]
+    Toml(#[from] toml::de::Error),
+}
+
+#[derive(Default, TypePath)]
+pub struct LightPaletteLoader;
+
+impl AssetLoader for LightPaletteLoader {
+    type Asset = LightPalette;
+    type Settings = ();
+    type Error = LightPaletteError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(toml::from_str(std::str::from_utf8(&bytes)?)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["palette.toml"]
+    }
+}
+
+/// [`System`] that copies the palette asset into the [`LightPalette`] [`Resource`] when it is
+/// loaded or changed, and recolors the beams.
+fn apply_light_palette(
+    mut ev_asset: MessageReader<AssetEvent<LightPalette>>,
+    handle: Res<LightPaletteHandle>,
+    palettes: Res<Assets<LightPalette>>,
+    mut palette: ResMut<LightPalette>,
+    render_data: Res<LightRenderData>,
+    mut materials: ResMut<Assets<LightMaterial>>,
+) {
+    let mut changed = false;
+    for ev in ev_asset.read() {
+        changed |= ev.is_loaded_with_dependencies(&handle.0) || ev.is_modified(&handle.0);
+    }
+    if !changed {
+        return;
+    }
+    let Some(loaded) = palettes.get(&handle.0) else {
+        return;
+    };
+
+    *palette = loaded.clone();
+    for (color, faded) in render_data.material_map.iter() {
+        for (step, material) in faded.iter().enumerate() {
+            if let Some(material) = materials.get_mut(&material.0) {
+                *material =
+                    LightMaterial::from(&palette[color]).with_energy(LightRenderData::fade(step));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_palette_matches_beam_layers() {
+        let palette = LightPalette::default();
+        assert_eq!(palette[LightColor::Purple].bounces, 2);
+
+        let blue = palette[LightColor::Blue].collision_layers();
+        assert_eq!(blue.memberships, LayerMask::from(Layers::BlueRay));
+        assert!(!blue.filters.has_all(Layers::BlueCrystal));
+        assert!(blue.filters.has_all(Layers::WhiteRay));
+
+        let black = palette[LightColor::Black].collision_layers();
+        assert!(!black.filters.has_all(Layers::WhiteRay));
+        assert!(!black.filters.has_all(Layers::BlackRay));
+        assert!(black.filters.has_all(Layers::Terrain));
+    }
+}
@@ -7,17 +7,41 @@ use bevy::{
 };
 use enum_map::{enum_map, EnumMap};
 
-use super::{LightColor, LIGHT_SEGMENT_THICKNESS};
+use super::{
+    emitter::HAZARD_BEAM_COLOR, palette::LightPalette, LightColor, LIGHT_SEGMENT_THICKNESS,
+};
 
 /// The path to the shader used by the [`LightMaterial`]
 const LIGHT_SHADER_PATH: &str = "shaders/light.wgsl";
 
+/// The number of materials each beam color has, for beams that are running out of energy
+const LIGHT_FADE_STEPS: usize = 8;
+
 /// A resource that stored handles to the [`Mesh2d`] and [`MeshMaterial2d`] used in the rendering
 /// of [`LightSegment`](super::segments::LightSegmentBundle)s.
 #[derive(Resource)]
 pub struct LightRenderData {
     pub mesh: Mesh2d,
-    pub material_map: EnumMap<LightColor, MeshMaterial2d<LightMaterial>>,
+    /// Materials of each color, from the faintest to full energy
+    pub material_map: EnumMap<LightColor, Vec<MeshMaterial2d<LightMaterial>>>,
+    /// Materials of the beams of [`HazardLaser`](super::emitter::HazardLaser)s
+    pub hazard_material: Vec<MeshMaterial2d<LightMaterial>>,
+}
+
+impl LightRenderData {
+    /// The opacity of the fade step `step`.
+    pub fn fade(step: usize) -> f32 {
+        (step + 1) as f32 / LIGHT_FADE_STEPS as f32
+    }
+
+    /// The material out of `materials` for a beam with `energy` (from 0 to 1) left.
+    pub fn faded(
+        materials: &[MeshMaterial2d<LightMaterial>],
+        energy: f32,
+    ) -> MeshMaterial2d<LightMaterial> {
+        let step = (energy * LIGHT_FADE_STEPS as f32).ceil() as usize;
+        materials[step.clamp(1, LIGHT_FADE_STEPS) - 1].clone()
+    }
 }
 
 impl FromWorld for LightRenderData {
@@ -28,17 +52,25 @@ impl FromWorld for LightRenderData {
             .add(Rectangle::new(1.0, LIGHT_SEGMENT_THICKNESS))
             .into();
 
+        let palette = world.resource::<LightPalette>().clone();
         let mut materials = world.resource_mut::<Assets<LightMaterial>>();
 
+        let mut add_faded = |material: LightMaterial| -> Vec<MeshMaterial2d<LightMaterial>> {
+            (0..LIGHT_FADE_STEPS)
+                .map(|step| {
+                    materials
+                        .add(material.clone().with_energy(Self::fade(step)))
+                        .into()
+                })
+                .collect()
+        };
+
         LightRenderData {
             mesh: mesh_handle,
             material_map: enum_map! {
-                LightColor::Green => materials.add(LightMaterial::from(LightColor::Green)).into(),
-                LightColor::Purple => materials.add(LightMaterial::from(LightColor::Purple)).into(),
-                LightColor::White => materials.add(LightMaterial::from(LightColor::White)).into(),
-                LightColor::Blue => materials.add(LightMaterial::from(LightColor::Blue)).into(),
-                // LightColor::Black => materials.add(LightMaterial::from(LightColor::Black)).into(),
+                color => add_faded(LightMaterial::from(&palette[color])),
             },
+            hazard_material: add_faded(LightMaterial::from(HAZARD_BEAM_COLOR)),
         }
     }
 }
@@ -52,6 +84,14 @@ pub struct LightMaterial {
     pub _wasm_padding: Vec2,
 }
 
+impl LightMaterial {
+    /// Fades the material out for a beam with `energy` (from 0 to 1) left.
+    pub fn with_energy(mut self, energy: f32) -> Self {
+        self.color.alpha = energy;
+        self
+    }
+}
+
 impl Material2d for LightMaterial {
     fn fragment_shader() -> ShaderRef {
         LIGHT_SHADER_PATH.into()
@@ -1,28 +1,40 @@
 use avian2d::prelude::*;
 use bevy::{
+    ecs::{entity::EntityHashSet, system::SystemParam},
     platform::collections::{HashMap, HashSet},
     prelude::*,
 };
+use enum_map::EnumMap;
 
 use crate::{
     camera::HIGHRES_LAYER,
     game::{
-        defs::mirror::Mirror,
+        defs::{
+            glass::GlassGroup, mirror::Mirror, one_way_platform::OneWayPlatform, portal::Portal,
+            prism::Prism, refractor::RefractorGroup, rotatable_mirror::MirrorSurface, DangerBox,
+        },
         light::{
+            emitter::{HazardBeam, HAZARD_BEAM_COLOR, HAZARD_LIGHTING_COLOR},
+            palette::{light_interaction_layers, LightPalette},
             render::{LightMaterial, LightRenderData},
-            HitByLight, LightBeamSource, LightColor, LIGHT_SPEED,
+            BlackRayComponent, HitByLight, LightBeamSource, LightColor, LIGHT_SPEED,
         },
         lighting::LineLight2d,
         lyra::beam::PlayerLightInventory,
         particle::spark::SparkExplosionEvent,
         Layers,
     },
+    ldtk::{EntityLevelParam, LdtkLevelParam, LevelExt},
     shared::ResetLevels,
 };
 
-/// Marker [`Component`] used to query for light segments.
-#[derive(Default, Component, Clone, Debug, PartialEq, Eq, Hash)]
+/// Marker [`Component`] used to query for light segments. Segments are identified by the
+/// [`LightBeamSource`] they belong to, so that several beams of the same color can coexist.
+#[derive(Component, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct LightSegment {
+    pub source: Entity,
+    /// The index of the branch of the beam, in depth first order
+    pub branch: usize,
     pub color: LightColor,
     pub index: usize,
 }
@@ -31,7 +43,7 @@ pub struct LightSegment {
 pub struct LightSegmentCache(HashMap<LightSegment, (Transform, Entity, Entity)>);
 
 /// [`Bundle`] used in the initialization of the [`LightSegmentCache`] to spawn segment entities.
-#[derive(Bundle, Debug, Clone, Default)]
+#[derive(Bundle, Debug, Clone)]
 pub struct LightSegmentBundle {
     pub segment: LightSegment,
     pub mesh: Mesh2d,
@@ -67,27 +79,40 @@ impl FromWorld for LightBounceSfx {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct LightBeamIntersection {
     pub entity: Entity,
     pub point: Vec2,
     pub time: f32,
+    /// The energy the beam has left as it leaves the intersection, from 0 to 1
+    pub energy: f32,
 }
 
-/// Stores information about the trajectory of a LightBeam
+/// Stores information about the trajectory of a LightBeam. When a beam is split (e.g. by a
+/// [`Prism`]), it continues as several `branches`, which all start at its last intersection. A beam
+/// that enters a [`Portal`] continues as a branch that starts at the exit portal, so that nothing
+/// is drawn across the gap.
 #[derive(Debug)]
 pub struct LightBeamPlayback {
+    pub color: LightColor,
+    pub start_pos: Vec2,
+    /// The time the beam had travelled when this branch started
+    pub start_time: f32,
+    /// The energy the beam had when this branch started, from 0 to 1
+    pub start_energy: f32,
+    /// How far the beam travels on full energy, which is infinite if the level doesn't limit it
+    pub range: f32,
     pub intersections: Vec<LightBeamIntersection>,
     pub end_point: Option<Vec2>,
+    /// If the branch ends because the beam ran out of energy, so it can't grow any further
+    pub out_of_energy: bool,
     pub elapsed_time: f32,
+    pub branches: Vec<LightBeamPlayback>,
 }
 
 impl LightBeamPlayback {
-    pub fn iter_points<'a>(
-        &'a self,
-        source: &'a LightBeamSource,
-    ) -> impl Iterator<Item = Vec2> + 'a {
-        std::iter::once(source.start_pos)
+    pub fn iter_points(&self) -> impl Iterator<Item = Vec2> + '_ {
+        std::iter::once(self.start_pos)
             .chain(
                 self.intersections
                     .iter()
@@ -95,85 +120,291 @@ impl LightBeamPlayback {
             )
             .chain(self.end_point.iter().copied())
     }
+
+    /// Iterates over this playback and all of its branches, depth first.
+    pub fn iter_branches(&self) -> impl Iterator<Item = &LightBeamPlayback> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let branch = stack.pop()?;
+            stack.extend(branch.branches.iter().rev());
+            Some(branch)
+        })
+    }
+
+    /// The time at which the furthest branch of the beam ends.
+    pub fn max_elapsed_time(&self) -> f32 {
+        self.iter_branches()
+            .map(|branch| branch.elapsed_time)
+            .fold(self.elapsed_time, f32::max)
+    }
 }
 
+/// The playback of a [`LightBeamSource`] from the previous frame, used to find which
+/// intersections were added or removed.
 #[derive(Default, Debug, Component)]
 pub struct PrevLightBeamPlayback {
+    pub color: LightColor,
     pub intersections: Vec<LightBeamIntersection>,
+    pub branches: Vec<PrevLightBeamPlayback>,
 }
 
 #[derive(Component)]
 pub struct LightBeamSourceDespawn;
 
-const LIGHT_MAX_SEGMENTS: usize = 15;
+/// Tracks whether a [`LightBeamSource`] needs to be recast. Once a beam stops growing it is
+/// stable, and it is only recast after [`mark_dirty_light_sources`] finds a change to a collider
+/// it touches or that is near one of its segments.
+#[derive(Component, Debug)]
+pub struct LightBeamState {
+    pub dirty: bool,
+    /// The time the stable beam takes to reach its furthest point
+    length: f32,
+    /// The entities the stable beam intersects
+    touching: EntityHashSet,
+    /// The bounding boxes of the stable beam's segments
+    bounds: Vec<Rect>,
+}
+
+impl Default for LightBeamState {
+    fn default() -> Self {
+        LightBeamState {
+            dirty: true,
+            length: 0.,
+            touching: EntityHashSet::default(),
+            bounds: vec![],
+        }
+    }
+}
+
+impl LightBeamState {
+    /// Updates the state after the beam was recast. The beam is stable if it didn't change since
+    /// the last frame, and every branch ends at an intersection, runs out of energy or leaves the
+    /// `level_box` (so it can't grow any further).
+    fn update(
+        &mut self,
+        playback: &LightBeamPlayback,
+        cut: Option<f32>,
+        branch_points: &[BranchPoints],
+        level_box: Option<Rect>,
+    ) {
+        self.dirty = cut.is_some()
+            || playback.iter_branches().any(|branch| {
+                branch.end_point.is_some_and(|end| {
+                    !branch.out_of_energy
+                        && level_box.is_none_or(|level_box| level_box.contains(end))
+                })
+            });
+        if self.dirty {
+            return;
+        }
+        self.length = playback.max_elapsed_time();
+        self.touching = playback
+            .iter_branches()
+            .flat_map(|branch| branch.intersections.iter().map(|x| x.entity))
+            .collect();
+        self.bounds = branch_points
+            .iter()
+            .flat_map(|(_, pts, _)| pts.windows(2))
+            .map(|pts| Rect::from_corners(pts[0], pts[1]).inflate(1.))
+            .collect();
+    }
+}
+
+/// The maximum number of segments of a beam, including extra bounces from mirrors.
+pub(crate) const LIGHT_MAX_SEGMENTS: usize = 15;
+
+/// The number of times a beam can be split, to keep facing prisms from splitting forever.
+const LIGHT_MAX_BRANCH_DEPTH: usize = 4;
+
+/// How far into a volume a cast starts, so that it doesn't hit the edge it came from.
+const VOLUME_NUDGE: f32 = 0.01;
+
+/// [`Resource`] with the `BeamRange` of the current level, which scales how far beams of each color
+/// travel before running out of energy. Beams are not limited if it is [`None`].
+#[derive(Resource, Default, Debug, PartialEq)]
+pub struct LevelBeamRange(pub Option<f32>);
+
+/// [`Resource`] with the `LightBridges` of the current level, the colors whose beams are one-way
+/// platforms Lyra can stand on.
+#[derive(Resource, Default, Debug, PartialEq)]
+pub struct LevelLightBridges(pub EnumMap<LightColor, bool>);
+
+/// [`System`] that keeps the [`LevelBeamRange`] and [`LevelLightBridges`] up to date with the
+/// current level, and recasts the beams when the range changes.
+pub fn update_level_light_fields(
+    ldtk_level_param: LdtkLevelParam,
+    mut beam_range: ResMut<LevelBeamRange>,
+    mut bridges: ResMut<LevelLightBridges>,
+    mut q_states: Query<&mut LightBeamState>,
+) {
+    let Some(level) = ldtk_level_param.cur_level() else {
+        return;
+    };
+    bridges.set_if_neq(LevelLightBridges(level.raw().light_bridges()));
+    if beam_range.set_if_neq(LevelBeamRange(level.raw().beam_range())) {
+        for mut state in q_states.iter_mut() {
+            state.dirty = true;
+        }
+    }
+}
+
+/// [`SystemParam`] with the queries [`play_light_beam`] uses to find out how a beam interacts with
+/// what it hits.
+#[derive(SystemParam)]
+pub struct LightInteractionParam<'w, 's> {
+    pub q_mirrors: Query<'w, 's, (), Or<(With<Mirror>, With<MirrorSurface>)>>,
+    pub q_prisms: Query<'w, 's, &'static Prism>,
+    pub q_glass: Query<'w, 's, &'static GlassGroup>,
+    pub q_refractors: Query<'w, 's, &'static RefractorGroup>,
+    pub q_portals: Query<'w, 's, (Entity, &'static Portal, &'static GlobalTransform)>,
+    pub q_black_ray: Query<'w, 's, (), With<BlackRayComponent>>,
+    pub levels: EntityLevelParam<'w, 's>,
+    pub palette: Res<'w, LightPalette>,
+    pub beam_range: Res<'w, LevelBeamRange>,
+}
+
+impl LightInteractionParam<'_, '_> {
+    /// How far a beam of `color` travels on full energy.
+    pub fn range(&self, color: LightColor) -> f32 {
+        self.beam_range
+            .0
+            .map_or(f32::INFINITY, |scale| self.palette[color].range * scale)
+    }
+
+    /// The energy a beam of `color` loses each time it bounces.
+    pub fn bounce_energy(&self, color: LightColor) -> f32 {
+        self.beam_range
+            .0
+            .map_or(0., |_| self.palette[color].bounce_energy)
+    }
+
+    /// The portal in the same level paired with the portal `entity`, and where a beam that hits
+    /// `entity` at `point` going in `dir` leaves it.
+    fn portal_exit(&self, entity: Entity, point: Vec2, dir: Dir2) -> Option<(Entity, Vec2, Dir2)> {
+        let (_, portal, transform) = self.q_portals.get(entity).ok()?;
+        let level = self.levels.level_of(entity);
+        let (exit, exit_portal, exit_transform) =
+            self.q_portals.iter().find(|(other, other_portal, _)| {
+                *other != entity
+                    && other_portal.pair_id == portal.pair_id
+                    && self.levels.level_of(*other) == level
+            })?;
+        let (exit_point, exit_dir) = portal.transport(
+            transform.translation().xy(),
+            exit_portal,
+            exit_transform.translation().xy(),
+            point,
+            dir,
+        )?;
+        Some((exit, exit_point, exit_dir))
+    }
+}
+
+/// Bends `dir` as it crosses a surface with `normal`, from a medium with refractive index `from`
+/// into one with `to`, following Snell's law. Returns [`None`] on total internal reflection.
+pub fn refract(dir: Dir2, normal: Vec2, from: f32, to: f32) -> Option<Dir2> {
+    // hits from inside a volume report the outward normal, so face it against the beam
+    let normal = if normal.dot(*dir) > 0. {
+        -normal
+    } else {
+        normal
+    };
+    let eta = from / to;
+    let cos_i = -normal.dot(*dir);
+    let k = 1. - eta * eta * (1. - cos_i * cos_i);
+    if k < 0. {
+        return None;
+    }
+    Some(Dir2::new(eta * *dir + (eta * cos_i - k.sqrt()) * normal).unwrap_or(dir))
+}
 
 pub fn play_light_beam(
     spatial_query: &SpatialQuery,
     source: &LightBeamSource,
-    // black_ray_qry: &Query<(Entity, &BlackRayComponent)>,
-    q_mirrors: &Query<&Mirror>,
+    interactions: &LightInteractionParam,
+) -> LightBeamPlayback {
+    play_light_branch(spatial_query, source, 0.0, 1.0, None, 0, interactions)
+}
+
+/// Plays a single branch of a beam. `source` describes where the branch starts, with the time
+/// left to travel as its `time_traveled`. The beam loses energy as it travels and bounces, and
+/// stops once it has none left.
+#[allow(clippy::too_many_arguments)]
+fn play_light_branch(
+    spatial_query: &SpatialQuery,
+    source: &LightBeamSource,
+    start_time: f32,
+    start_energy: f32,
+    excluded: Option<Entity>,
+    depth: usize,
+    interactions: &LightInteractionParam,
 ) -> LightBeamPlayback {
     let mut ray_pos = source.start_pos;
     let mut ray_dir = source.start_dir;
-    let collision_groups = match source.color {
-        LightColor::White => CollisionLayers::new(
-            Layers::WhiteRay,
-            [
-                Layers::Terrain,
-                Layers::LightSensor,
-                Layers::Spike,
-                Layers::BlueCrystal,
-                Layers::Platform,
-            ],
-        ),
-        // LightColor::Black => {
-        //     CollisionLayers::new(Layers::BlackRay, Layers::Terrain, Layers::LightSensor)
-        // }
-        LightColor::Blue => CollisionLayers::new(
-            Layers::BlueRay,
-            [
-                Layers::Terrain,
-                Layers::LightSensor,
-                Layers::WhiteRay,
-                Layers::Spike,
-                Layers::Platform,
-            ],
-        ),
-        _ => CollisionLayers::new(
-            Layers::LightRay,
-            [
-                Layers::Terrain,
-                Layers::LightSensor,
-                Layers::WhiteRay,
-                Layers::Spike,
-                Layers::BlueCrystal,
-                Layers::Platform,
-            ],
-        ),
-    };
+    let collision_groups = interactions.palette[source.color].collision_layers();
 
-    let mut ray_qry = SpatialQueryFilter::default().with_mask(collision_groups.filters);
+    // the entities the next cast skips. Branches start on the prism, glass or portal that split
+    // them, so they shouldn't hit it again until they bounce off something else
+    let mut excluded_entities: Vec<Entity> = excluded.into_iter().collect();
+    // the refractive volume the beam just left, which only the next cast skips so that the beam
+    // can enter it again later
+    let mut left_medium: Option<Entity> = None;
     let mut remaining_time = source.time_traveled;
+    let range = interactions.range(source.color);
+    let mut energy = start_energy;
 
     let mut playback = LightBeamPlayback {
+        color: source.color,
+        start_pos: source.start_pos,
+        start_time,
+        start_energy,
+        range,
         intersections: vec![],
         end_point: None,
-        elapsed_time: 0.0,
+        out_of_energy: false,
+        elapsed_time: start_time,
+        branches: vec![],
     };
 
-    let num_segments = source.color.num_bounces() + 1;
+    let num_segments = interactions.palette[source.color].bounces + 1;
 
     let mut i = 0;
     let mut extra_bounces_from_mirror = 0;
-    while i < num_segments + extra_bounces_from_mirror && i < LIGHT_MAX_SEGMENTS {
-        let Some(hit) = spatial_query.cast_ray(ray_pos, ray_dir, remaining_time, true, &ray_qry)
-        else {
-            let final_point = ray_pos + ray_dir * remaining_time;
-            playback.elapsed_time += remaining_time;
+    // the number of times the beam passed through glass or was refracted, which doesn't count as a
+    // bounce
+    let mut passes = 0;
+    // the refractive volume the beam is travelling through, with its refractive index
+    let mut medium: Option<(Entity, f32)> = None;
+    while i < num_segments + extra_bounces_from_mirror
+        && i < LIGHT_MAX_SEGMENTS
+        && passes < LIGHT_MAX_SEGMENTS
+    {
+        if energy <= 0. {
+            playback.out_of_energy = true;
+            break;
+        }
+        // inside a volume, the cast is hollow so that it finds the edge where the beam leaves
+        let nudge = if medium.is_some() { VOLUME_NUDGE } else { 0. };
+        let max_distance = remaining_time.min(energy * range);
+        let ray_qry = SpatialQueryFilter::default()
+            .with_mask(collision_groups.filters)
+            .with_excluded_entities(excluded_entities.iter().copied().chain(left_medium.take()));
+        let Some(mut hit) = spatial_query.cast_ray(
+            ray_pos + *ray_dir * nudge,
+            ray_dir,
+            (max_distance - nudge).max(0.),
+            medium.is_none(),
+            &ray_qry,
+        ) else {
+            let final_point = ray_pos + ray_dir * max_distance;
+            playback.elapsed_time += max_distance;
             playback.end_point = Some(final_point);
+            playback.out_of_energy = max_distance < remaining_time;
             break;
         };
-        if q_mirrors.contains(hit.entity) {
+        hit.distance += nudge;
+        energy -= hit.distance / range;
+        if interactions.q_mirrors.contains(hit.entity) {
             extra_bounces_from_mirror += 1;
         }
 
@@ -185,234 +416,691 @@ pub fn play_light_beam(
 
         playback.elapsed_time += hit.distance;
         remaining_time -= hit.distance;
-        let hit_point = ray_pos + *ray_dir * hit.distance;
+        let mut hit_point = ray_pos + *ray_dir * hit.distance;
+
+        if let Ok(refractor) = interactions.q_refractors.get(hit.entity) {
+            let leaving = medium.is_some_and(|(entity, _)| entity == hit.entity);
+            ray_pos = hit_point;
+            passes += 1;
+
+            // merged tiles split a body of water into several volumes, so the beam goes straight
+            // across the seams between them
+            if leaving {
+                let probe = hit_point + *ray_dir * VOLUME_NUDGE;
+                let filter =
+                    SpatialQueryFilter::default().with_mask([Layers::Terrain, Layers::Water]);
+                let next = spatial_query
+                    .point_intersections(probe, &filter)
+                    .into_iter()
+                    .find(|entity| {
+                        *entity != hit.entity
+                            && interactions.q_refractors.get(*entity).is_ok_and(|next| {
+                                next.refractive_index == refractor.refractive_index
+                            })
+                    });
+                if let Some(next) = next {
+                    medium = Some((next, refractor.refractive_index));
+                    continue;
+                }
+            }
+
+            playback.intersections.push(LightBeamIntersection {
+                entity: hit.entity,
+                point: hit_point,
+                time: playback.elapsed_time,
+                energy,
+            });
+
+            let from = medium.map_or(1., |(_, index)| index);
+            let to = if leaving {
+                1.
+            } else {
+                refractor.refractive_index
+            };
+            match refract(ray_dir, hit.normal, from, to) {
+                Some(dir) if leaving => {
+                    ray_dir = dir;
+                    medium = None;
+                    left_medium = Some(hit.entity);
+                }
+                Some(dir) => {
+                    ray_dir = dir;
+                    medium = Some((hit.entity, to));
+                }
+                // total internal reflection keeps the beam on the side it came from
+                None => {
+                    ray_dir = Dir2::new(Vec2::from(ray_dir).reflect(hit.normal))
+                        .expect("cast dir cannot be 0");
+                }
+            }
+            continue;
+        }
+
+        let glass = interactions.q_glass.get(hit.entity).ok();
+        match glass.map(|glass| glass.0.transmit(source.color)) {
+            // glass that doesn't change the beam is passed straight through
+            Some(Some(color)) if color == source.color => {
+                ray_pos = hit_point;
+                excluded_entities.push(hit.entity);
+                passes += 1;
+                continue;
+            }
+            // converters recolor the beam where it leaves them, so it crosses the glass first
+            Some(Some(_)) => {
+                let inside = (max_distance - hit.distance).max(0.);
+                let crossed = spatial_query
+                    .cast_ray_predicate(
+                        hit_point + *ray_dir * VOLUME_NUDGE,
+                        ray_dir,
+                        (inside - VOLUME_NUDGE).max(0.),
+                        false,
+                        &SpatialQueryFilter::default(),
+                        &|entity| entity == hit.entity,
+                    )
+                    .map_or(inside, |exit| exit.distance + VOLUME_NUDGE);
+                playback.elapsed_time += crossed;
+                remaining_time -= crossed;
+                energy -= crossed / range;
+                hit_point += *ray_dir * crossed;
+            }
+            _ => {}
+        }
 
         playback.intersections.push(LightBeamIntersection {
             entity: hit.entity,
             point: hit_point,
             time: playback.elapsed_time,
+            energy,
         });
 
+        // beams that cross a black beam are absorbed by it
+        if interactions.q_black_ray.contains(hit.entity) {
+            break;
+        }
+
+        // beams that are split, recolored or sent through a portal end here, and continue as
+        // branches that start on `branch_entity`
+        let split = if let Ok(prism) = interactions.q_prisms.get(hit.entity) {
+            Some((hit.entity, hit_point, prism.split(source.color, ray_dir)))
+        } else if let Some((exit, exit_point, exit_dir)) =
+            interactions.portal_exit(hit.entity, hit_point, ray_dir)
+        {
+            Some((exit, exit_point, vec![(source.color, exit_dir)]))
+        } else {
+            glass.map(|glass| {
+                let split = glass
+                    .0
+                    .transmit(source.color)
+                    .map(|color| (color, ray_dir))
+                    .into_iter()
+                    .collect();
+                (hit.entity, hit_point, split)
+            })
+        };
+
+        if let Some((branch_entity, branch_pos, split)) = split {
+            if depth < LIGHT_MAX_BRANCH_DEPTH {
+                for (color, dir) in split {
+                    let branch_source = LightBeamSource {
+                        start_pos: branch_pos,
+                        start_dir: dir,
+                        time_traveled: remaining_time,
+                        color,
+                    };
+                    playback.branches.push(play_light_branch(
+                        spatial_query,
+                        &branch_source,
+                        playback.elapsed_time,
+                        energy,
+                        Some(branch_entity),
+                        depth + 1,
+                        interactions,
+                    ));
+                }
+            }
+            break;
+        }
+
+        energy -= interactions.bounce_energy(source.color);
+        if let Some(last) = playback.intersections.last_mut() {
+            last.energy = energy.max(0.);
+        }
+
         ray_pos = hit_point;
         ray_dir =
             Dir2::new((Vec2::from(ray_dir)).reflect(hit.normal)).expect("cast dir cannot be 0");
         if ignore_entity {
-            ray_qry = ray_qry.with_excluded_entities([hit.entity]);
+            excluded_entities = vec![hit.entity];
         }
 
-        // if black_ray_qry.get(hit.entity).is_ok() {
-        //     break;
-        // }
         i += 1;
     }
 
     playback
 }
 
+/// A change in what a beam hits, found by [`diff_light_playback`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LightBeamChange {
+    Hit {
+        intersection: LightBeamIntersection,
+        color: LightColor,
+        /// The index of the intersection in its branch, if a bounce sound should be played
+        bounce: Option<usize>,
+    },
+    Unhit {
+        entity: Entity,
+        color: LightColor,
+    },
+}
+
+/// Finds the earliest time at which `new` differs from `prev`. The beam is rewound to this point
+/// and grows back from there, so that intersections are added one at a time.
+fn find_light_playback_cut(prev: &PrevLightBeamPlayback, new: &LightBeamPlayback) -> Option<f32> {
+    let same_color = prev.color == new.color;
+    for i in 0.. {
+        match (prev.intersections.get(i), new.intersections.get(i)) {
+            (Some(px), Some(nx)) if same_color && px.entity == nx.entity => continue,
+            (Some(px), Some(nx)) => return Some(px.time.min(nx.time)),
+            (Some(px), None) => return Some(px.time),
+            (None, Some(nx)) => return Some(nx.time),
+            (None, None) => break,
+        }
+    }
+
+    // the intersections are the same, so the change can only be in the branches
+    let mut cut: Option<f32> = None;
+    for j in 0..prev.branches.len().max(new.branches.len()) {
+        let branch_cut = match (prev.branches.get(j), new.branches.get(j)) {
+            (Some(prev_branch), Some(new_branch)) => {
+                find_light_playback_cut(prev_branch, new_branch)
+            }
+            (Some(prev_branch), None) => prev_branch.intersections.first().map(|x| x.time),
+            (None, Some(new_branch)) => new_branch.intersections.first().map(|x| x.time),
+            (None, None) => None,
+        };
+        cut = match (cut, branch_cut) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+    }
+    cut
+}
+
+/// Records that everything in `prev` from the intersection at `from` onwards is no longer hit.
+fn unhit_light_playback(
+    prev: &PrevLightBeamPlayback,
+    from: usize,
+    changes: &mut Vec<LightBeamChange>,
+) {
+    for intersection in prev.intersections.iter().skip(from) {
+        changes.push(LightBeamChange::Unhit {
+            entity: intersection.entity,
+            color: prev.color,
+        });
+    }
+    for branch in prev.branches.iter() {
+        unhit_light_playback(branch, 0, changes);
+    }
+}
+
+/// The color and points of a branch of a beam to render, with the energy the beam has in the
+/// middle of each segment between the points.
+type BranchPoints = (LightColor, Vec<Vec2>, Vec<f32>);
+
+/// Follows the energy of a branch along its points, to find the energy of each segment.
+struct BranchEnergy {
+    range: f32,
+    energy: f32,
+    segments: Vec<f32>,
+}
+
+impl BranchEnergy {
+    fn new(playback: &LightBeamPlayback) -> Self {
+        BranchEnergy {
+            range: playback.range,
+            energy: playback.start_energy,
+            segments: vec![],
+        }
+    }
+
+    /// Adds the segment from `from` to `to`, with `left` as the energy after `to` if it is known.
+    fn push(&mut self, from: Vec2, to: Vec2, left: Option<f32>) {
+        let used = from.distance(to) / self.range;
+        self.segments.push((self.energy - used / 2.).clamp(0., 1.));
+        self.energy = left.unwrap_or(self.energy - used);
+    }
+}
+
+/// Updates `prev` to the part of `new` that is before the time `cut`, recording which entities
+/// were hit or unhit. The points of each branch to render are pushed to `branch_points` in depth
+/// first order.
+fn diff_light_playback(
+    prev: &mut PrevLightBeamPlayback,
+    new: &LightBeamPlayback,
+    cut: Option<f32>,
+    changes: &mut Vec<LightBeamChange>,
+    branch_points: &mut Vec<BranchPoints>,
+) {
+    let cut_time = cut.unwrap_or(f32::INFINITY);
+    let same_color = prev.color == new.color;
+    let mut pts = vec![new.start_pos];
+    let mut energy = BranchEnergy::new(new);
+    let mut last_time = new.start_time;
+
+    let mut i = 0;
+    loop {
+        let prev_x = prev.intersections.get(i).copied();
+        let new_x = new.intersections.get(i).copied();
+
+        if let (Some(px), Some(nx)) = (prev_x, new_x) {
+            if same_color && px.entity == nx.entity && nx.time <= cut_time {
+                // keep on updating the previous intersection buffer because this could be a
+                // moving platform
+                prev.intersections[i] = nx;
+                energy.push(
+                    *pts.last().expect("points are never empty"),
+                    nx.point,
+                    Some(nx.energy),
+                );
+                pts.push(nx.point);
+                last_time = nx.time;
+                i += 1;
+                continue;
+            }
+        }
+
+        if prev_x.is_none() && new_x.is_none() {
+            break;
+        }
+
+        // handle remove before add because it could be the case that both are true
+        if prev_x.is_some() {
+            unhit_light_playback(prev, i, changes);
+            prev.intersections.truncate(i);
+            prev.branches.clear();
+        }
+
+        if let Some(nx) = new_x.filter(|nx| nx.time <= cut_time) {
+            changes.push(LightBeamChange::Hit {
+                intersection: nx,
+                color: new.color,
+                bounce: prev_x.is_none().then_some(i),
+            });
+            prev.intersections.push(nx);
+            energy.push(
+                *pts.last().expect("points are never empty"),
+                nx.point,
+                Some(nx.energy),
+            );
+            pts.push(nx.point);
+        } else if let Some(to) = new_x.map(|nx| nx.point).or(new.end_point) {
+            // the beam was rewound, so it ends where it was at the time of the cut
+            let from = *pts.last().expect("points are never empty");
+            let to = from + (to - from).normalize_or_zero() * (cut_time - last_time);
+            energy.push(from, to, None);
+            pts.push(to);
+        }
+        prev.color = new.color;
+        branch_points.push((new.color, pts, energy.segments));
+        return;
+    }
+
+    prev.color = new.color;
+    if let Some(end_point) = new.end_point {
+        let from = *pts.last().expect("points are never empty");
+        let to = if new.elapsed_time > cut_time {
+            from + (end_point - from).normalize_or_zero() * (cut_time - last_time)
+        } else {
+            end_point
+        };
+        energy.push(from, to, None);
+        pts.push(to);
+    }
+    branch_points.push((new.color, pts, energy.segments));
+
+    if prev.branches.len() > new.branches.len() {
+        for extra in prev.branches.drain(new.branches.len()..) {
+            unhit_light_playback(&extra, 0, changes);
+        }
+    }
+    for (j, new_branch) in new.branches.iter().enumerate() {
+        if prev.branches.len() <= j {
+            prev.branches.push(PrevLightBeamPlayback {
+                color: new_branch.color,
+                ..default()
+            });
+        }
+        diff_light_playback(
+            &mut prev.branches[j],
+            new_branch,
+            cut,
+            changes,
+            branch_points,
+        );
+    }
+}
+
+/// [`System`] that marks stable [`LightBeamSource`]s as dirty when a collider that beams can hit
+/// changes (e.g. a crystal toggling [`ColliderDisabled`] or a platform moving) in a way that could
+/// affect them.
+#[allow(clippy::type_complexity)]
+pub fn mark_dirty_light_sources(
+    mut q_light_sources: Query<(Entity, &mut LightBeamState)>,
+    q_changed: Query<
+        (
+            Entity,
+            &ColliderAabb,
+            Option<&CollisionLayers>,
+            Option<&LightSegment>,
+        ),
+        Or<(
+            Changed<Position>,
+            Changed<Rotation>,
+            Changed<Collider>,
+            Changed<CollisionLayers>,
+            Added<ColliderDisabled>,
+        )>,
+    >,
+    q_colliders: Query<(
+        Entity,
+        &ColliderAabb,
+        Option<&CollisionLayers>,
+        Option<&LightSegment>,
+    )>,
+    mut removed_disabled: RemovedComponents<ColliderDisabled>,
+    mut removed_colliders: RemovedComponents<Collider>,
+) {
+    let light_layers = light_interaction_layers();
+    let changed: Vec<(Entity, Rect, Option<Entity>)> = q_changed
+        .iter()
+        .chain(q_colliders.iter_many(removed_disabled.read()))
+        .filter(|(_, _, layers, _)| {
+            layers.is_none_or(|layers| layers.memberships & light_layers != LayerMask::NONE)
+        })
+        .map(|(entity, aabb, _, segment)| {
+            (
+                entity,
+                Rect::from_corners(aabb.min, aabb.max),
+                segment.map(|segment| segment.source),
+            )
+        })
+        .collect();
+    let removed: Vec<Entity> = removed_colliders.read().collect();
+    if changed.is_empty() && removed.is_empty() {
+        return;
+    }
+
+    for (source_entity, mut state) in q_light_sources.iter_mut() {
+        if state.dirty {
+            continue;
+        }
+        let touched_removed = removed.iter().any(|entity| state.touching.contains(entity));
+        // a beam's own segments only move when it is recast
+        let touched_changed = changed
+            .iter()
+            .filter(|(_, _, source)| *source != Some(source_entity))
+            .any(|(entity, rect, _)| {
+                state.touching.contains(entity)
+                    || state
+                        .bounds
+                        .iter()
+                        .any(|bounds| !bounds.intersect(*rect).is_empty())
+            });
+        state.dirty = touched_removed || touched_changed;
+    }
+}
+
 /// [`System`] that runs on [`Update`], calculating the [`Transform`] of light segments from the
-/// corresponding [`LightBeamSource`]. Note that this calculation happens every frame, so instead of
-/// rapidly spawning/despawning the entities, we spawn them and cache them in the
-/// [`LightSegmentCache`], then modify their [`Visibility`] and [`Transform`]s.
+/// corresponding [`LightBeamSource`]. Instead of rapidly spawning/despawning the entities, we spawn
+/// them and cache them in the [`LightSegmentCache`], then modify their [`Visibility`] and
+/// [`Transform`]s.
 ///
-/// If needed, optimization work can be done by recalculating only segments that are currently
-/// changing (segments already "stabilized" usually won't move).
-#[allow(clippy::too_many_arguments)]
+/// Only dirty sources are recast. The segments of stable sources are left as they are until
+/// [`mark_dirty_light_sources`] finds a change that could affect them.
+///
+/// The segments of [`HazardBeam`]s are [`DangerBox`]es instead of interacting with other beams, and
+/// their hits don't trigger [`HitByLight`]. Segments of colors in the [`LevelLightBridges`] get a
+/// child [`OneWayPlatform`] that Lyra can stand on.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn simulate_light_sources(
     mut commands: Commands,
-    mut q_light_sources: Query<(&mut LightBeamSource, &mut PrevLightBeamPlayback)>,
-    // q_black_ray: Query<(Entity, &BlackRayComponent)>,
+    mut q_light_sources: Query<(
+        Entity,
+        &mut LightBeamSource,
+        &mut PrevLightBeamPlayback,
+        &mut LightBeamState,
+        Has<LightBeamSourceDespawn>,
+        Has<HazardBeam>,
+    )>,
     spatial_query: SpatialQuery,
-    q_mirrors: Query<&Mirror>,
+    light_interactions: LightInteractionParam,
     // used to tell if a collision was against a white beam (a different sound is played)
     q_segments: Query<&LightSegment>,
     light_bounce_sfx: Res<LightBounceSfx>,
     mut ev_spark_explosion: MessageWriter<SparkExplosionEvent>,
     light_render_data: Res<LightRenderData>,
     mut segment_cache: ResMut<LightSegmentCache>,
+    bridges: Res<LevelLightBridges>,
+    ldtk_level_param: LdtkLevelParam,
 ) {
+    let level_box = ldtk_level_param
+        .cur_level()
+        .map(|level| level.raw().level_box());
     let mut all_segments = segment_cache
         .iter()
         .map(|(k, _)| k.clone())
         .collect::<HashSet<LightSegment>>();
 
-    for (mut source, mut prev_playback) in q_light_sources.iter_mut() {
-        let playback = play_light_beam(&spatial_query, &source, &q_mirrors);
-        let mut pts: Vec<Vec2> = playback.iter_points(&source).collect();
-        source.time_traveled = playback.elapsed_time;
+    let mut stable_sources = EntityHashSet::default();
 
-        let mut i = 0;
-        loop {
-            let prev_x = prev_playback.intersections.get(i).cloned();
-            let new_x = playback.intersections.get(i).cloned();
-
-            let is_same_intersection = match (prev_x, new_x) {
-                (Some(px), Some(nx)) => px.entity == nx.entity,
-                (None, None) => break,
-                _ => false,
-            };
-
-            // diff intersection
-            if !is_same_intersection {
-                let is_closer = match (prev_x, new_x) {
-                    (None, Some(_)) => true,
-                    (Some(px), Some(nx)) => px.time > nx.time,
-                    (Some(_), None) => false,
-                    _ => break,
-                };
-
-                // remvoe all points after the current intersection
-                pts.truncate(i + 2);
-
-                let add_intersection = prev_x.is_none() || is_closer;
-                let remove_intersection = prev_x.is_some();
-                let play_sound = prev_x.is_none() && !is_same_intersection;
-
-                // handle remove before add because it could be the case that both are true
-                if remove_intersection {
-                    let prev_x = prev_x.unwrap();
-                    pts[i + 1] = prev_x.point;
-                    source.time_traveled = prev_x.time;
+    for (source_entity, mut source, mut prev_playback, mut state, despawning, hazard) in
+        q_light_sources.iter_mut()
+    {
+        if !state.dirty && !despawning {
+            source.time_traveled = state.length;
+            stable_sources.insert(source_entity);
+            continue;
+        }
 
-                    // unhit current + future lights
-                    for j in i..prev_playback.intersections.len() {
-                        commands.trigger(HitByLight {
-                            entity: prev_playback.intersections[j].entity,
-                            color: source.color,
-                            hit: false,
-                        });
-                    }
-                    //leaves [0, i-1]
-                    prev_playback.intersections.truncate(i);
-                }
+        let playback = play_light_beam(&spatial_query, &source, &light_interactions);
+        let cut = find_light_playback_cut(&prev_playback, &playback);
+        source.time_traveled = cut.unwrap_or_else(|| playback.max_elapsed_time());
 
-                if add_intersection {
-                    let new_x = new_x.unwrap();
-                    pts[i + 1] = new_x.point;
-                    source.time_traveled = new_x.time;
+        let mut changes = Vec::new();
+        let mut branch_points = Vec::new();
+        diff_light_playback(
+            &mut prev_playback,
+            &playback,
+            cut,
+            &mut changes,
+            &mut branch_points,
+        );
+        state.update(&playback, cut, &branch_points, level_box);
 
+        for change in changes {
+            match change {
+                LightBeamChange::Unhit { .. } if hazard => {}
+                LightBeamChange::Unhit { entity, color } => {
                     commands.trigger(HitByLight {
-                        entity: new_x.entity,
-                        color: source.color,
-                        hit: true,
+                        entity,
+                        color,
+                        hit: false,
                     });
-                    assert!(i == prev_playback.intersections.len());
-                    prev_playback.intersections.push(new_x);
                 }
+                LightBeamChange::Hit {
+                    intersection,
+                    color,
+                    bounce,
+                } => {
+                    if !hazard {
+                        commands.trigger(HitByLight {
+                            entity: intersection.entity,
+                            color,
+                            hit: true,
+                        });
+                    }
 
-                if play_sound {
-                    let new_x = new_x.unwrap();
-                    let reflect = match q_segments.get(new_x.entity) {
+                    let Some(bounce) = bounce else {
+                        continue;
+                    };
+                    let reflect = match q_segments.get(intersection.entity) {
                         Ok(segment) => segment.color == LightColor::White,
                         _ => false,
                     };
                     let audio = if reflect {
                         light_bounce_sfx
                             .reflect
-                            .get(i)
+                            .get(bounce)
                             .unwrap_or(&light_bounce_sfx.reflect[2])
                     } else {
                         light_bounce_sfx
                             .bounce
-                            .get(i)
+                            .get(bounce)
                             .unwrap_or(&light_bounce_sfx.bounce[2])
                     }
                     .clone();
                     ev_spark_explosion.write(SparkExplosionEvent {
-                        pos: new_x.point,
-                        color: source.color.light_beam_color(),
+                        pos: intersection.point,
+                        color: if hazard {
+                            HAZARD_BEAM_COLOR
+                        } else {
+                            light_interactions.palette[color].light_beam_color()
+                        },
                     });
                     commands
-                        .entity(new_x.entity)
+                        .entity(intersection.entity)
                         .with_child((AudioPlayer::new(audio), PlaybackSettings::DESPAWN));
                 }
-
-                break;
-            } else {
-                // keep on updating the previous intersection buffer because this could be a moving
-                // platform
-                prev_playback.intersections[i] = new_x.unwrap();
             }
-            i += 1;
         }
 
-        for i in 0..pts.len() - 1 {
-            if pts[i].distance(pts[i + 1]) < 0.1 {
-                continue;
-            }
-            // NOTE: hardcode here should be okay
-            let midpoint = pts[i].midpoint(pts[i + 1]).extend(4.);
-            let scale = Vec3::new(pts[i].distance(pts[i + 1]), 1., 1.);
-            let rotation = (pts[i + 1] - pts[i]).to_angle();
-
-            let transform = Transform::from_translation(midpoint)
-                .with_scale(scale)
-                .with_rotation(Quat::from_rotation_z(rotation));
-
-            let segment = LightSegment {
-                color: source.color,
-                index: i,
+        for (branch, (color, pts, energy)) in branch_points.into_iter().enumerate() {
+            let (materials, lighting_color) = if hazard {
+                (&light_render_data.hazard_material, HAZARD_LIGHTING_COLOR)
+            } else {
+                (
+                    &light_render_data.material_map[color],
+                    light_interactions.palette[color].lighting_color(),
+                )
             };
+            for i in 0..pts.len() - 1 {
+                if pts[i].distance(pts[i + 1]) < 0.1 {
+                    continue;
+                }
+                // beams fade out as they run out of energy
+                let material = LightRenderData::faded(materials, energy[i]);
+                let light_color = lighting_color.extend(energy[i]);
+                // NOTE: hardcode here should be okay
+                let midpoint = pts[i].midpoint(pts[i + 1]).extend(4.);
+                let scale = Vec3::new(pts[i].distance(pts[i + 1]), 1., 1.);
+                let rotation = (pts[i + 1] - pts[i]).to_angle();
 
-            let (entity, light_entity) = match segment_cache.get(&segment) {
-                None => {
-                    let seg = commands
-                        .spawn(transform)
-                        .insert(LightSegmentBundle {
-                            segment: LightSegment {
-                                color: source.color,
-                                index: i,
-                            },
-                            mesh: light_render_data.mesh.clone(),
-                            material: light_render_data.material_map[source.color].clone(),
-                            visibility: Visibility::Visible,
-                            transform,
-                        })
-                        .insert(HIGHRES_LAYER)
-                        .id();
-
-                    let light = commands
-                        .spawn(LineLight2d {
-                            color: source.color.lighting_color().extend(1.0),
+                let transform = Transform::from_translation(midpoint)
+                    .with_scale(scale)
+                    .with_rotation(Quat::from_rotation_z(rotation));
+
+                let segment = LightSegment {
+                    source: source_entity,
+                    branch,
+                    color,
+                    index: i,
+                };
+
+                let (entity, light_entity) = match segment_cache.get(&segment) {
+                    None => {
+                        let seg = commands
+                            .spawn(transform)
+                            .insert(LightSegmentBundle {
+                                segment: segment.clone(),
+                                mesh: light_render_data.mesh.clone(),
+                                material,
+                                visibility: Visibility::Visible,
+                                transform,
+                            })
+                            .insert(HIGHRES_LAYER)
+                            .id();
+
+                        let light = commands
+                            .spawn(LineLight2d {
+                                color: light_color,
+                                half_length: scale.x / 2.0,
+                                radius: 20.0,
+                                volumetric_intensity: 0.04,
+                            })
+                            .insert(ChildOf(seg))
+                            .id();
+
+                        match color {
+                            _ if hazard => {
+                                commands.entity(seg).insert((
+                                    Collider::rectangle(1., 1.),
+                                    Sensor,
+                                    CollisionLayers::new(Layers::DangerBox, Layers::PlayerHurtbox),
+                                    DangerBox,
+                                ));
+                            }
+                            LightColor::White => {
+                                commands.entity(seg).insert((
+                                    Collider::rectangle(1., 1.),
+                                    Sensor,
+                                    CollisionLayers::new(
+                                        Layers::WhiteRay,
+                                        [Layers::LightRay, Layers::BlueRay],
+                                    ),
+                                ));
+                            }
+                            LightColor::Black => {
+                                commands.entity(seg).insert((
+                                    Collider::rectangle(1., 1.),
+                                    Sensor,
+                                    CollisionLayers::new(
+                                        Layers::BlackRay,
+                                        [Layers::LightRay, Layers::BlueRay, Layers::WhiteRay],
+                                    ),
+                                    BlackRayComponent,
+                                ));
+                            }
+                            _ => {}
+                        }
+                        if bridges.0[color] && !hazard {
+                            commands.spawn((
+                                Collider::rectangle(1., 1.),
+                                CollisionLayers::new(Layers::LightBridge, Layers::PlayerCollider),
+                                OneWayPlatform::default(),
+                                ActiveCollisionHooks::MODIFY_CONTACTS,
+                                Friction::new(0.),
+                                Transform::default(),
+                                ChildOf(seg),
+                            ));
+                        }
+                        all_segments.remove(&segment);
+                        (seg, light)
+                    }
+                    Some((t, e, le)) => {
+                        all_segments.remove(&segment);
+                        if *t == transform {
+                            continue;
+                        }
+                        commands.entity(*le).try_insert(LineLight2d {
+                            color: light_color,
                             half_length: scale.x / 2.0,
                             radius: 20.0,
                             volumetric_intensity: 0.04,
-                        })
-                        .insert(ChildOf(seg))
-                        .id();
-
-                    if source.color == LightColor::White {
-                        commands.entity(seg).insert((
-                            Collider::rectangle(1., 1.),
-                            Sensor,
-                            CollisionLayers::new(
-                                Layers::WhiteRay,
-                                [Layers::LightRay, Layers::BlueRay],
-                            ),
-                        ));
-                    }
-                    all_segments.remove(&segment);
-                    (seg, light)
-                }
-                Some((t, e, le)) => {
-                    all_segments.remove(&segment);
-                    if *t == transform {
-                        continue;
+                        });
+                        commands.entity(*e).try_insert((transform, material));
+                        (*e, *le)
                     }
-                    commands.entity(*le).try_insert(LineLight2d {
-                        color: source.color.lighting_color().extend(1.0),
-                        half_length: scale.x / 2.0,
-                        radius: 20.0,
-                        volumetric_intensity: 0.04,
-                    });
-                    commands.entity(*e).try_insert(transform);
-                    (*e, *le)
-                }
-            };
-            segment_cache.insert(segment.clone(), (transform, entity, light_entity));
+                };
+                segment_cache.insert(segment.clone(), (transform, entity, light_entity));
+            }
         }
     }
 
     for segment in all_segments {
+        if stable_sources.contains(&segment.source) {
+            continue;
+        }
         commands.entity(segment_cache[&segment].1).despawn();
         segment_cache.remove(&segment);
     }
@@ -429,7 +1117,9 @@ pub fn tick_light_sources(
             source.time_traveled -= LIGHT_SPEED * time.delta_secs() * 64.;
             if source.time_traveled <= 0.0 {
                 commands.entity(entity).despawn();
-                lyra.collectible[source.color] = None;
+                // sources that aren't in lyra's inventory (e.g. from emitters) are left alone
+                lyra.collectible[source.color]
+                    .retain(|inventory_source| inventory_source.entity != entity);
             }
         } else {
             source.time_traveled += LIGHT_SPEED * time.delta_secs() * 64.;
@@ -452,3 +1142,242 @@ pub fn cleanup_light_sources(
     }
     cache.clear();
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy_ecs_ldtk::LevelIid;
+
+    use super::*;
+
+    fn intersection(entity: Entity, time: f32) -> LightBeamIntersection {
+        LightBeamIntersection {
+            entity,
+            point: Vec2::new(time, 0.),
+            time,
+            energy: 1.,
+        }
+    }
+
+    fn playback(
+        intersections: Vec<LightBeamIntersection>,
+        start_time: f32,
+        branches: Vec<LightBeamPlayback>,
+    ) -> LightBeamPlayback {
+        let elapsed_time = intersections.last().map_or(start_time, |x| x.time);
+        LightBeamPlayback {
+            color: LightColor::Green,
+            start_pos: Vec2::new(start_time, 0.),
+            start_time,
+            start_energy: 1.,
+            range: f32::INFINITY,
+            intersections,
+            end_point: None,
+            out_of_energy: false,
+            elapsed_time,
+            branches,
+        }
+    }
+
+    #[test]
+    fn refraction_follows_snells_law() {
+        let dir = Dir2::new(Vec2::new(1., -1.)).unwrap();
+        let refracted = refract(dir, Vec2::Y, 1., 1.5).unwrap();
+        let sin_in = std::f32::consts::FRAC_1_SQRT_2;
+        assert!((refracted.x * 1.5 - sin_in).abs() < 1e-5);
+        assert!(refracted.y < 0.);
+
+        // leaving glass at 45 degrees is past the critical angle
+        assert_eq!(refract(dir, Vec2::Y, 1.5, 1.), None);
+    }
+
+    #[test]
+    fn beam_is_stable_once_it_stops_changing() {
+        let mut world = World::new();
+        let wall = world.spawn_empty().id();
+        let mut state = LightBeamState::default();
+
+        let mut growing = playback(vec![], 0., vec![]);
+        growing.end_point = Some(Vec2::new(5., 0.));
+        state.update(&growing, None, &[], Some(Rect::new(0., -10., 10., 10.)));
+        assert!(state.dirty);
+
+        // a beam that left the level can't hit anything else
+        growing.end_point = Some(Vec2::new(15., 0.));
+        state.update(&growing, None, &[], Some(Rect::new(0., -10., 10., 10.)));
+        assert!(!state.dirty);
+
+        let stable = playback(vec![intersection(wall, 10.)], 0., vec![]);
+        state.update(&stable, Some(0.), &[], None);
+        assert!(state.dirty);
+
+        let points = [(
+            LightColor::Green,
+            vec![Vec2::ZERO, Vec2::new(10., 0.)],
+            vec![1.],
+        )];
+        state.update(&stable, None, &points, None);
+        assert!(!state.dirty);
+        assert_eq!(state.length, 10.);
+        assert!(state.touching.contains(&wall));
+        assert!(state.bounds[0].contains(Vec2::new(5., 0.5)));
+    }
+
+    #[test]
+    fn beam_fades_as_it_runs_out_of_energy() {
+        let mut world = World::new();
+        let wall = world.spawn_empty().id();
+
+        // the beam loses half of its energy bouncing off of the wall
+        let mut new = playback(vec![intersection(wall, 4.)], 0., vec![]);
+        new.range = 10.;
+        new.intersections[0].energy = 0.5;
+        new.end_point = Some(Vec2::new(4., 5.));
+        new.out_of_energy = true;
+
+        let mut prev = PrevLightBeamPlayback::default();
+        let mut branch_points = Vec::new();
+        diff_light_playback(&mut prev, &new, None, &mut Vec::new(), &mut branch_points);
+
+        let (_, _, energy) = &branch_points[0];
+        assert!((energy[0] - 0.8).abs() < 1e-5);
+        assert!((energy[1] - 0.25).abs() < 1e-5);
+
+        // a beam that ran out of energy can't grow any further
+        let mut state = LightBeamState::default();
+        state.update(&new, None, &branch_points, None);
+        assert!(!state.dirty);
+    }
+
+    #[test]
+    fn new_intersection_is_hit() {
+        let mut world = World::new();
+        let wall = world.spawn_empty().id();
+
+        let mut prev = PrevLightBeamPlayback::default();
+        let new = playback(vec![intersection(wall, 5.)], 0., vec![]);
+
+        let cut = find_light_playback_cut(&prev, &new);
+        assert_eq!(cut, Some(5.));
+
+        let mut changes = Vec::new();
+        let mut branch_points = Vec::new();
+        diff_light_playback(&mut prev, &new, cut, &mut changes, &mut branch_points);
+
+        assert_eq!(
+            changes,
+            vec![LightBeamChange::Hit {
+                intersection: intersection(wall, 5.),
+                color: LightColor::Green,
+                bounce: Some(0),
+            }]
+        );
+        assert_eq!(prev.intersections, vec![intersection(wall, 5.)]);
+    }
+
+    #[test]
+    fn earlier_change_in_branch_rewinds_other_branches() {
+        let mut world = World::new();
+        let prism = world.spawn_empty().id();
+        let far_wall = world.spawn_empty().id();
+        let near_wall = world.spawn_empty().id();
+
+        let mut prev = PrevLightBeamPlayback {
+            color: LightColor::Green,
+            intersections: vec![intersection(prism, 4.)],
+            branches: vec![
+                PrevLightBeamPlayback {
+                    color: LightColor::Green,
+                    intersections: vec![intersection(far_wall, 10.)],
+                    branches: vec![],
+                },
+                PrevLightBeamPlayback {
+                    color: LightColor::Green,
+                    ..default()
+                },
+            ],
+        };
+        let new = playback(
+            vec![intersection(prism, 4.)],
+            0.,
+            vec![
+                playback(vec![intersection(far_wall, 10.)], 4., vec![]),
+                playback(vec![intersection(near_wall, 6.)], 4., vec![]),
+            ],
+        );
+
+        let cut = find_light_playback_cut(&prev, &new);
+        assert_eq!(cut, Some(6.));
+
+        let mut changes = Vec::new();
+        let mut branch_points = Vec::new();
+        diff_light_playback(&mut prev, &new, cut, &mut changes, &mut branch_points);
+
+        assert_eq!(
+            changes,
+            vec![
+                LightBeamChange::Unhit {
+                    entity: far_wall,
+                    color: LightColor::Green,
+                },
+                LightBeamChange::Hit {
+                    intersection: intersection(near_wall, 6.),
+                    color: LightColor::Green,
+                    bounce: Some(0),
+                },
+            ]
+        );
+        assert!(prev.branches[0].intersections.is_empty());
+        assert_eq!(prev.branches[1].intersections.len(), 1);
+        assert_eq!(branch_points.len(), 3);
+    }
+
+    #[test]
+    fn portals_pair_within_their_level() {
+        let mut world = World::new();
+        world.init_resource::<LightPalette>();
+        world.init_resource::<LevelBeamRange>();
+
+        let portal = |angle| Portal {
+            pair_id: 0,
+            angle,
+            length: 16.,
+        };
+        let level = world.spawn(LevelIid::new("level")).id();
+        let layer = world.spawn(ChildOf(level)).id();
+        let other_level = world.spawn(LevelIid::new("other_level")).id();
+
+        // the next level reuses the pair_id
+        world.spawn((portal(0.), GlobalTransform::default(), ChildOf(other_level)));
+        world.spawn((
+            portal(PI),
+            GlobalTransform::from_xyz(0., 50., 0.),
+            ChildOf(other_level),
+        ));
+        let entry = world
+            .spawn((
+                portal(0.),
+                GlobalTransform::from_xyz(100., 0., 0.),
+                ChildOf(layer),
+            ))
+            .id();
+        let exit = world
+            .spawn((
+                portal(PI),
+                GlobalTransform::from_xyz(100., 50., 0.),
+                ChildOf(layer),
+            ))
+            .id();
+
+        let found = world
+            .run_system_once(move |interactions: LightInteractionParam| {
+                interactions
+                    .portal_exit(entry, Vec2::new(100., 0.), Dir2::NEG_X)
+                    .map(|(exit, _, _)| exit)
+            })
+            .unwrap();
+        assert_eq!(found, Some(exit));
+    }
+}
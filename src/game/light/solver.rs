@@ -0,0 +1,413 @@
+//! A headless version of the light simulation in
+//! [`play_light_beam`](super::segments::play_light_beam) that works on an [`LdtkProject`]
+//! directly, without a running [`App`]. It is used to test puzzle behavior and by tools that need
+//! to know where Lyra's beams go.
+//!
+//! The terrain, spikes, platforms, crystals (in their current state), mirrors and sensors of a
+//! level are modelled. Glass, prisms, portals, refractive volumes and moving platforms are not,
+//! and beams don't interact with each other.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{
+    ldtk::{Level, Type},
+    prelude::*,
+};
+
+use crate::game::{
+    defs::{
+        crystal::{Crystal, CrystalColor},
+        diagonal::{DiagonalKind, DiagonalTile},
+        rotatable_mirror::RotatableMirror,
+        sensor::{LightSensor, SensorMode, SensorRequirement},
+    },
+    light::{palette::LightPalette, segments::LIGHT_MAX_SEGMENTS, LightColor},
+    Layers,
+};
+
+const TERRAIN_LAYER_IDENT: &str = "Terrain";
+const ENTITY_LAYER_IDENT: &str = "Entities";
+
+/// How far a reflected beam is moved off the surface it bounced from before it is cast again, so
+/// that it doesn't hit the surface (or the cell next to it) again.
+const SOLVER_NUDGE: f32 = 0.01;
+
+/// What a [`SolverCollider`] is in the level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolverObject {
+    Terrain,
+    Spike,
+    Platform,
+    Mirror,
+    Crystal {
+        color: CrystalColor,
+        active: bool,
+    },
+    /// A sensor, by its index in [`LightSolverWorld::sensors`]
+    Sensor(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct SolverCollider {
+    pub object: SolverObject,
+    pub collider: Collider,
+    pub position: Vec2,
+    pub rotation: Rotation,
+}
+
+/// A light sensor in a [`LightSolverWorld`].
+#[derive(Debug, Clone)]
+pub struct SolverSensor {
+    pub iid: String,
+    pub position: Vec2,
+    pub toggle_color: CrystalColor,
+    pub event_id: Option<i32>,
+    pub requirement: SensorRequirement,
+    pub mode: SensorMode,
+}
+
+/// The path of a beam through a [`LightSolverWorld`].
+#[derive(Debug, Clone, Default)]
+pub struct SolvedBeam {
+    /// The start of the beam, followed by every point where it bounced, and its end point
+    pub points: Vec<Vec2>,
+    /// The object the beam hit at each point after the start, if any
+    pub hits: Vec<Option<SolverObject>>,
+    /// The indices of the sensors hit by the beam, in the order they were hit
+    pub sensors_hit: Vec<usize>,
+}
+
+/// The colliders of a single level that beams can interact with, in world coordinates (the same
+/// coordinates as [`LevelExt::start_flag_pos`](crate::ldtk::LevelExt::start_flag_pos)).
+#[derive(Debug, Clone, Default)]
+pub struct LightSolverWorld {
+    pub bounds: Rect,
+    pub colliders: Vec<SolverCollider>,
+    pub sensors: Vec<SolverSensor>,
+    /// Defines how beams of each color behave, which is the built in palette by default
+    pub palette: LightPalette,
+}
+
+impl LightSolverWorld {
+    /// Builds the world of the level with the given iid, or [`None`] if the project has no such
+    /// level.
+    pub fn from_project(project: &LdtkProject, level_iid: &str) -> Option<Self> {
+        project
+            .json_data()
+            .levels
+            .iter()
+            .find(|level| level.iid == level_iid)
+            .map(Self::from_level)
+    }
+
+    pub fn from_level(level: &Level) -> Self {
+        let origin = Vec2::new(level.world_x as f32, -level.world_y as f32);
+        let mut world = LightSolverWorld {
+            bounds: Rect::new(
+                origin.x,
+                origin.y,
+                origin.x + level.px_wid as f32,
+                origin.y - level.px_hei as f32,
+            ),
+            ..default()
+        };
+
+        let layers = level.layer_instances.as_ref().expect("Layers not found! (This is probably because you are using the \"Separate level files\" option.)");
+        for layer in layers {
+            if layer.identifier == TERRAIN_LAYER_IDENT && layer.layer_instance_type == Type::IntGrid
+            {
+                let grid = layer.grid_size as f32;
+                for (y, row) in layer.int_grid_csv.chunks(layer.c_wid as usize).enumerate() {
+                    // runs of the same rectangular cell are merged into one collider, so that
+                    // there are less colliders to cast against
+                    for (x, run) in runs(row) {
+                        let (x, y, len) = (x as f32, y as f32, run as f32);
+                        let center = origin + Vec2::new(x + len / 2., -(y + 0.5)) * grid;
+                        world.add_cell(row[x as usize], center, Vec2::new(len, 1.) * grid);
+                    }
+                }
+            } else if layer.identifier == ENTITY_LAYER_IDENT {
+                for entity in &layer.entity_instances {
+                    world.add_entity(entity, origin);
+                }
+            }
+        }
+        world
+    }
+
+    fn add_cell(&mut self, value: i32, center: Vec2, size: Vec2) {
+        let half = size.y / 2.;
+        let (object, collider) = match value {
+            1 => (SolverObject::Terrain, Collider::rectangle(size.x, size.y)),
+            2 => (
+                SolverObject::Spike,
+                Collider::triangle(
+                    Vec2::new(-half, -half),
+                    Vec2::new(half, -half),
+                    Vec2::new(0., half),
+                ),
+            ),
+            3..=10 => {
+                let crystal = Crystal::from(IntGridCell { value });
+                (
+                    SolverObject::Crystal {
+                        color: crystal.color,
+                        active: crystal.active,
+                    },
+                    Collider::rectangle(size.x, size.y),
+                )
+            }
+            15 => (
+                SolverObject::Platform,
+                Collider::compound(vec![(
+                    Vec2::new(0.0, half - 0.25),
+                    Rotation::default(),
+                    Collider::rectangle(size.x, 0.5),
+                )]),
+            ),
+            16 => (SolverObject::Mirror, Collider::rectangle(size.x, size.y)),
+            27..=34 => {
+                let tile = DiagonalTile::from(IntGridCell { value });
+                let [a, b, c] = tile.corner.triangle(half);
+                let object = match tile.kind {
+                    DiagonalKind::Terrain => SolverObject::Terrain,
+                    DiagonalKind::Mirror => SolverObject::Mirror,
+                };
+                (object, Collider::triangle(a, b, c))
+            }
+            _ => return,
+        };
+        self.colliders.push(SolverCollider {
+            object,
+            collider,
+            position: center,
+            rotation: Rotation::default(),
+        });
+    }
+
+    fn add_entity(&mut self, entity: &EntityInstance, origin: Vec2) {
+        let size = Vec2::new(entity.width as f32, entity.height as f32);
+        // entities are placed by their pivot, and spawned at their center
+        let offset = (Vec2::splat(0.5) - entity.pivot) * size;
+        let position = origin
+            + Vec2::new(entity.px.x as f32, -entity.px.y as f32)
+            + Vec2::new(offset.x, -offset.y);
+
+        match entity.identifier.as_str() {
+            "Sensor" => {
+                let sensor = LightSensor::from(entity);
+                self.colliders.push(SolverCollider {
+                    object: SolverObject::Sensor(self.sensors.len()),
+                    collider: Collider::rectangle(8., 8.),
+                    position,
+                    rotation: Rotation::default(),
+                });
+                self.sensors.push(SolverSensor {
+                    iid: entity.iid.clone(),
+                    position,
+                    toggle_color: sensor.toggle_color,
+                    event_id: sensor.event_id,
+                    requirement: sensor.requirement,
+                    mode: sensor.mode,
+                });
+            }
+            "RotatableMirror" => {
+                let mirror = RotatableMirror::from(entity);
+                self.colliders.push(SolverCollider {
+                    object: SolverObject::Mirror,
+                    collider: Collider::rectangle(mirror.length, 2.),
+                    position,
+                    rotation: Rotation::radians(mirror.angle),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Toggles all crystals of the given color, like a [`LightSensor`] without an `event_id` does
+    /// when it is activated.
+    pub fn toggle_crystals(&mut self, color: CrystalColor) {
+        for collider in self.colliders.iter_mut() {
+            if let SolverObject::Crystal {
+                color: crystal_color,
+                active,
+            } = &mut collider.object
+            {
+                if *crystal_color == color {
+                    *active = !*active;
+                }
+            }
+        }
+    }
+
+    /// If beams with the given filters hit the object, like they would its collider in game.
+    fn blocks(object: SolverObject, filters: LayerMask) -> bool {
+        let layer = match object {
+            SolverObject::Terrain | SolverObject::Mirror => Layers::Terrain,
+            SolverObject::Spike => Layers::Spike,
+            SolverObject::Platform => Layers::Platform,
+            SolverObject::Sensor(_) => Layers::LightSensor,
+            SolverObject::Crystal { active: false, .. } => return false,
+            SolverObject::Crystal {
+                color: CrystalColor::Blue,
+                ..
+            } => Layers::BlueCrystal,
+            SolverObject::Crystal { .. } => Layers::Terrain,
+        };
+        filters.has_all(layer)
+    }
+
+    fn cast_ray(
+        &self,
+        origin: Vec2,
+        dir: Dir2,
+        max_distance: f32,
+        color: LightColor,
+    ) -> Option<(usize, f32, Vec2)> {
+        let filters = self.palette[color].collision_layers().filters;
+        self.colliders
+            .iter()
+            .enumerate()
+            .filter(|(_, collider)| Self::blocks(collider.object, filters))
+            .filter_map(|(i, collider)| {
+                collider
+                    .collider
+                    .cast_ray(
+                        collider.position,
+                        collider.rotation,
+                        origin,
+                        *dir,
+                        max_distance,
+                        true,
+                    )
+                    .map(|(distance, normal)| (i, distance, normal))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Plays a beam of `color` from `start_pos` in `start_dir` until it runs out of bounces. Like in
+    /// the simulation, every surface reflects the beam, and mirrors give it an extra bounce.
+    pub fn solve(&self, start_pos: Vec2, start_dir: Dir2, color: LightColor) -> SolvedBeam {
+        let max_distance = self.bounds.size().length();
+        let mut beam = SolvedBeam {
+            points: vec![start_pos],
+            ..default()
+        };
+
+        let mut ray_pos = start_pos;
+        let mut ray_dir = start_dir;
+        let mut num_segments = self.palette[color].bounces + 1;
+        let mut nudge = 0.;
+
+        let mut i = 0;
+        while i < num_segments && i < LIGHT_MAX_SEGMENTS {
+            let Some((index, distance, normal)) =
+                self.cast_ray(ray_pos + *ray_dir * nudge, ray_dir, max_distance, color)
+            else {
+                beam.points.push(ray_pos + *ray_dir * max_distance);
+                beam.hits.push(None);
+                break;
+            };
+            let object = self.colliders[index].object;
+            ray_pos += *ray_dir * (distance + nudge);
+            beam.points.push(ray_pos);
+            beam.hits.push(Some(object));
+
+            match object {
+                SolverObject::Mirror => num_segments += 1,
+                SolverObject::Sensor(sensor) => beam.sensors_hit.push(sensor),
+                _ => {}
+            }
+
+            ray_dir = Dir2::new(Vec2::from(ray_dir).reflect(normal)).expect("cast dir cannot be 0");
+            nudge = SOLVER_NUDGE;
+            i += 1;
+        }
+
+        beam
+    }
+}
+
+/// Splits a row of intgrid values into `(start, length)` runs. Rectangular cells of the same value
+/// next to each other form a single run, and every other cell is a run of its own.
+fn runs(row: &[i32]) -> Vec<(usize, usize)> {
+    let mergeable = |value: i32| matches!(value, 1 | 3..=10 | 15 | 16);
+    let mut runs: Vec<(usize, usize)> = vec![];
+    for (x, value) in row.iter().enumerate() {
+        match runs.last_mut() {
+            Some((start, len)) if mergeable(*value) && row[*start] == *value => {
+                *len += 1;
+            }
+            _ => runs.push((x, 1)),
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wall(position: Vec2, object: SolverObject) -> SolverCollider {
+        SolverCollider {
+            object,
+            collider: Collider::rectangle(8., 8.),
+            position,
+            rotation: Rotation::default(),
+        }
+    }
+
+    #[test]
+    fn rows_are_merged_into_runs() {
+        assert_eq!(
+            runs(&[0, 1, 1, 1, 2, 2, 16, 3, 3]),
+            vec![(0, 1), (1, 3), (4, 1), (5, 1), (6, 1), (7, 2)]
+        );
+    }
+
+    #[test]
+    fn beam_bounces_into_sensor() {
+        let mut world = LightSolverWorld {
+            bounds: Rect::new(0., 0., 200., 200.),
+            colliders: vec![
+                wall(Vec2::new(100., 4.), SolverObject::Terrain),
+                wall(Vec2::new(196., 100.), SolverObject::Sensor(0)),
+            ],
+            sensors: vec![SolverSensor {
+                iid: String::new(),
+                position: Vec2::new(196., 100.),
+                toggle_color: CrystalColor::Pink,
+                event_id: None,
+                requirement: SensorRequirement::default(),
+                mode: SensorMode::Hold,
+            }],
+            ..default()
+        };
+
+        let dir = Dir2::new(Vec2::new(1., -1.)).unwrap();
+        let beam = world.solve(Vec2::new(8., 100.), dir, LightColor::Green);
+        assert_eq!(beam.sensors_hit, vec![0]);
+        assert_eq!(beam.points.len(), 3);
+        assert!(beam.points[1].distance(Vec2::new(100., 8.)) < 1e-3);
+
+        // an active crystal in the way blocks the beam, until it is toggled
+        world.colliders.push(wall(
+            Vec2::new(150., 62.),
+            SolverObject::Crystal {
+                color: CrystalColor::Red,
+                active: true,
+            },
+        ));
+        assert!(world
+            .solve(Vec2::new(8., 100.), dir, LightColor::Green)
+            .sensors_hit
+            .is_empty());
+        world.toggle_crystals(CrystalColor::Red);
+        assert_eq!(
+            world
+                .solve(Vec2::new(8., 100.), dir, LightColor::Green)
+                .sensors_hit,
+            vec![0]
+        );
+    }
+}
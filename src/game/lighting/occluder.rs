@@ -35,6 +35,7 @@ use bevy::{
     sprite_render::{init_mesh_2d_pipeline, Mesh2dPipeline},
 };
 use bytemuck::{Pod, Zeroable};
+use std::f32::consts::FRAC_PI_2;
 
 use crate::game::lighting::render::{post_process_layout, DeferredLighting2d};
 
@@ -124,16 +125,35 @@ impl Default for Occluder2dGroups {
 #[component(on_add = add_visibility_class::<Occluder2d>)]
 pub struct Occluder2d {
     pub half_size: Vec2,
+    pub shape: Occluder2dShape,
 }
 
 impl Occluder2d {
     pub fn new(half_x: f32, half_y: f32) -> Self {
         Self {
             half_size: Vec2::new(half_x, half_y),
+            shape: Occluder2dShape::Rectangle,
+        }
+    }
+
+    pub fn triangle(half_x: f32, half_y: f32, quarter_turns: u32) -> Self {
+        Self {
+            half_size: Vec2::new(half_x, half_y),
+            shape: Occluder2dShape::Triangle { quarter_turns },
         }
     }
 }
 
+/// The shape of an [`Occluder2d`] within its `half_size`.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Occluder2dShape {
+    #[default]
+    Rectangle,
+    /// Right triangle with its right angle in the bottom left corner, turned counterclockwise by
+    /// `quarter_turns` quarter turns
+    Triangle { quarter_turns: u32 },
+}
+
 pub fn calculate_occluder_2d_bounds(
     mut commands: Commands,
     q_light_changed: Query<(Entity, &Occluder2d), Changed<Occluder2d>>,
@@ -148,7 +168,7 @@ pub fn calculate_occluder_2d_bounds(
 }
 
 impl ExtractComponent for Occluder2d {
-    type Out = (ExtractOccluder2d, Occluder2dBounds);
+    type Out = (ExtractOccluder2d, Occluder2dBounds, Occluder2dShape);
     type QueryData = (
         &'static GlobalTransform,
         &'static Occluder2d,
@@ -163,7 +183,10 @@ impl ExtractComponent for Occluder2d {
             return None;
         }
         // FIXME: should not do calculations in extract
-        let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+        let (scale, mut rotation, translation) = transform.to_scale_rotation_translation();
+        if let Occluder2dShape::Triangle { quarter_turns } = occluder.shape {
+            rotation *= Quat::from_rotation_z(FRAC_PI_2 * quarter_turns as f32);
+        }
         let transform_no_scale =
             Affine3A::from_scale_rotation_translation(scale.signum(), rotation, translation);
         let affine = Affine3::from(&transform_no_scale);
@@ -180,6 +203,7 @@ impl ExtractComponent for Occluder2d {
                 transform: transform.compute_transform(),
                 half_size: occluder.half_size,
             },
+            occluder.shape,
         ))
     }
 }
@@ -236,6 +260,7 @@ pub struct Occluder2dBuffers {
 }
 
 const OCCLUDER_2D_NUM_INDICES: u32 = 18;
+const OCCLUDER_2D_TRIANGLE_NUM_INDICES: u32 = 12;
 
 static VERTICES: [Occluder2dVertex; 8] = [
     Occluder2dVertex::new(vec3(-1.0, -1.0, 0.0), vec3(-1.0, 0.0, 0.0)),
@@ -250,6 +275,20 @@ static VERTICES: [Occluder2dVertex; 8] = [
 
 static INDICES: [u32; 18] = [0, 1, 2, 2, 3, 4, 4, 5, 6, 6, 7, 0, 0, 2, 4, 4, 6, 0];
 
+// the hypotenuse normal of the triangle occluder
+const DIAGONAL: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+static TRIANGLE_VERTICES: [Occluder2dVertex; 6] = [
+    Occluder2dVertex::new(vec3(-1.0, -1.0, 0.0), vec3(-1.0, 0.0, 0.0)),
+    Occluder2dVertex::new(vec3(-1.0, -1.0, 0.0), vec3(0.0, -1.0, 0.0)),
+    Occluder2dVertex::new(vec3(1.0, -1.0, 0.0), vec3(0.0, -1.0, 0.0)),
+    Occluder2dVertex::new(vec3(1.0, -1.0, 0.0), vec3(DIAGONAL, DIAGONAL, 0.0)),
+    Occluder2dVertex::new(vec3(-1.0, 1.0, 0.0), vec3(DIAGONAL, DIAGONAL, 0.0)),
+    Occluder2dVertex::new(vec3(-1.0, 1.0, 0.0), vec3(-1.0, 0.0, 0.0)),
+];
+
+static TRIANGLE_INDICES: [u32; 12] = [0, 1, 2, 2, 3, 4, 4, 5, 0, 0, 2, 4];
+
 impl FromWorld for Occluder2dBuffers {
     fn from_world(world: &mut World) -> Self {
         let render_device = world.resource::<RenderDevice>();
@@ -258,10 +297,11 @@ impl FromWorld for Occluder2dBuffers {
         let mut vbo = RawBufferVec::new(BufferUsages::VERTEX);
         let mut ibo = RawBufferVec::new(BufferUsages::INDEX);
 
-        for vtx in &VERTICES {
+        // the triangle is stored after the rectangle, and drawn with offsets in `DrawOccluder2d`
+        for vtx in VERTICES.iter().chain(&TRIANGLE_VERTICES) {
             vbo.push(*vtx);
         }
-        for index in &INDICES {
+        for index in INDICES.iter().chain(&TRIANGLE_INDICES) {
             ibo.push(*index);
         }
 
@@ -380,12 +420,12 @@ pub struct DrawOccluder2d;
 impl<P: PhaseItem> RenderCommand<P> for DrawOccluder2d {
     type Param = SRes<Occluder2dBuffers>;
     type ViewQuery = ();
-    type ItemQuery = ();
+    type ItemQuery = Read<Occluder2dShape>;
 
     fn render<'w>(
         _item: &P,
         _view: ROQueryItem<'w, '_, Self::ViewQuery>,
-        _entity: Option<ROQueryItem<'w, '_, Self::ItemQuery>>,
+        entity: Option<ROQueryItem<'w, '_, Self::ItemQuery>>,
         param: SystemParamItem<'w, '_, Self::Param>,
         pass: &mut TrackedRenderPass<'w>,
     ) -> RenderCommandResult {
@@ -397,7 +437,16 @@ impl<P: PhaseItem> RenderCommand<P> for DrawOccluder2d {
             0,
             IndexFormat::Uint32,
         );
-        pass.draw_indexed(0..OCCLUDER_2D_NUM_INDICES, 0, 0..1);
+        match entity.copied().unwrap_or_default() {
+            Occluder2dShape::Rectangle => {
+                pass.draw_indexed(0..OCCLUDER_2D_NUM_INDICES, 0, 0..1);
+            }
+            Occluder2dShape::Triangle { .. } => pass.draw_indexed(
+                OCCLUDER_2D_NUM_INDICES..OCCLUDER_2D_NUM_INDICES + OCCLUDER_2D_TRIANGLE_NUM_INDICES,
+                VERTICES.len() as i32,
+                0..1,
+            ),
+        }
 
         RenderCommandResult::Success
     }
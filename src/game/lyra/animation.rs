@@ -5,6 +5,7 @@ use crate::game::{
     animation::AnimationConfig,
     lyra::{
         controller::{movement, Grounded, MovementInfo},
+        zipline::Zipline,
         Lyra,
     },
     LevelSystems,
@@ -40,6 +41,8 @@ pub enum PlayerAnimationType {
     Jump,
     Fall,
     Land,
+    /// Hanging from a beam she is riding
+    Zipline,
 }
 
 // HAIR, LEFT, RIGHT
@@ -99,6 +102,8 @@ impl From<PlayerAnimationType> for AnimationConfig {
             PlayerAnimationType::Jump => AnimationConfig::new(15, 20, 24, false),
             PlayerAnimationType::Fall => AnimationConfig::new(21, 24, 24, false),
             PlayerAnimationType::Land => AnimationConfig::new(25, 28, 18, false),
+            // the last fall frame, which has her arms up
+            PlayerAnimationType::Zipline => AnimationConfig::new(24, 24, 1, false),
         }
     }
 }
@@ -144,14 +149,17 @@ pub fn set_animation(
             &mut PlayerAnimationType,
             &LinearVelocity,
             Has<Grounded>,
+            Has<Zipline>,
         ),
         With<Lyra>,
     >,
     mut was_grounded: Local<bool>,
 ) {
-    let (movement, mut config, mut animation, lin_vel, is_grounded) = player.into_inner();
+    let (movement, mut config, mut animation, lin_vel, is_grounded, riding) = player.into_inner();
 
-    let new_anim = if !is_grounded && lin_vel.0.y > 0.0 {
+    let new_anim = if riding {
+        PlayerAnimationType::Zipline
+    } else if !is_grounded && lin_vel.0.y > 0.0 {
         PlayerAnimationType::Jump
     } else if !is_grounded {
         PlayerAnimationType::Fall
@@ -13,9 +13,12 @@ use crate::{
     config::Config,
     game::{
         cursor::CursorWorldCoords,
-        defs::mirror::Mirror,
         light::{
-            segments::{play_light_beam, LightBeamSourceDespawn, PrevLightBeamPlayback},
+            palette::LightPalette,
+            segments::{
+                play_light_beam, LightBeamSourceDespawn, LightInteractionParam,
+                PrevLightBeamPlayback,
+            },
             LightBeamSource, LightColor,
         },
         lighting::LineLight2d,
@@ -25,11 +28,12 @@ use crate::{
         },
         Layers, LevelSystems,
     },
+    ldtk::{LdtkLevelParam, LevelExt},
     save::SaveParam,
     shared::{ResetLevels, UiState},
 };
 
-const NUM_INCREMENTS: i32 = 16; // The number of angle increments for light beam alignment
+pub const NUM_INCREMENTS: i32 = 16; // The number of angle increments for light beam alignment
 
 pub struct BeamControllerPlugin;
 
@@ -59,9 +63,13 @@ impl Plugin for BeamControllerPlugin {
 pub fn reset_light_inventory(
     _: On<ResetLevels>,
     mut inventory: Single<&mut PlayerLightInventory, With<Lyra>>,
+    ldtk_level_param: LdtkLevelParam,
 ) {
-    for (_, source) in inventory.collectible.iter_mut() {
-        *source = None;
+    for (_, sources) in inventory.collectible.iter_mut() {
+        sources.clear();
+    }
+    if let Some(level) = ldtk_level_param.cur_level() {
+        inventory.charges = level.raw().light_charges();
     }
 }
 
@@ -102,6 +110,7 @@ pub fn init_player_light_save_data(
         light_save_data.unlocked.insert(LightColor::Green);
         light_save_data.unlocked.insert(LightColor::Purple);
         light_save_data.unlocked.insert(LightColor::Blue);
+        light_save_data.unlocked.insert(LightColor::Black);
     }
 }
 
@@ -113,10 +122,12 @@ pub fn on_collide_beam_source(
     let Ok(beam_source) = q_beam_source.get(event.collider2) else {
         return;
     };
-    inventory.collectible[beam_source.color]
-        .as_mut()
-        .unwrap()
-        .in_reach = true;
+    if let Some(source) = inventory.collectible[beam_source.color]
+        .iter_mut()
+        .find(|source| source.entity == event.collider2)
+    {
+        source.in_reach = true;
+    }
 }
 
 pub fn on_leave_beam_source(
@@ -127,16 +138,18 @@ pub fn on_leave_beam_source(
     let Ok(beam_source) = q_beam_source.get(event.collider2) else {
         return;
     };
-    inventory.collectible[beam_source.color]
-        .as_mut()
-        .unwrap()
-        .in_reach = false;
+    if let Some(source) = inventory.collectible[beam_source.color]
+        .iter_mut()
+        .find(|source| source.entity == event.collider2)
+    {
+        source.in_reach = false;
+    }
 }
 
 #[derive(Debug)]
 pub struct LightInventorySource {
     in_reach: bool,
-    entity: Entity,
+    pub entity: Entity,
 }
 
 #[derive(Component, Default, Debug)]
@@ -147,7 +160,10 @@ pub struct PlayerLightInventory {
     pub current_color: Option<LightColor>,
     /// Is true if the color is unlocked
     pub allowed: EnumMap<LightColor, bool>,
-    pub collectible: EnumMap<LightColor, Option<LightInventorySource>>,
+    /// The number of beams of each color that can be out at once, set by the current level
+    pub charges: EnumMap<LightColor, usize>,
+    /// The beams of each color that are currently out
+    pub collectible: EnumMap<LightColor, Vec<LightInventorySource>>,
 
     pub use_iid: Option<LevelIid>,
     pub use_order: Vec<LightColor>,
@@ -163,8 +179,11 @@ impl PlayerLightInventory {
             allowed: enum_map! {
                 _ => false,
             },
+            charges: enum_map! {
+                _ => 1,
+            },
             collectible: enum_map! {
-                _ => None,
+                _ => Vec::new(),
             },
             use_iid: None,
             use_order: Vec::new(),
@@ -179,7 +198,7 @@ impl PlayerLightInventory {
     }
 
     pub fn can_shoot_color(&self, color: LightColor) -> bool {
-        self.allowed[color] && self.collectible[color].is_none()
+        self.allowed[color] && self.collectible[color].len() < self.charges[color]
     }
 }
 
@@ -199,23 +218,17 @@ pub fn handle_color_switch(
     mut ev_scroll: MessageReader<MouseWheel>,
     mut beam_actions: MessageWriter<BeamAction>,
     inventory: Single<&PlayerLightInventory, With<Lyra>>,
+    palette: Res<LightPalette>,
 ) {
-    static COLOR_BINDS: [(KeyCode, LightColor); 4] = [
-        (KeyCode::Digit1, LightColor::Green),
-        (KeyCode::Digit2, LightColor::Purple),
-        (KeyCode::Digit3, LightColor::White),
-        (KeyCode::Digit4, LightColor::Blue),
-        // (KeyCode::Digit5, LightColor::Black),
-    ];
-
-    let mut cur_index = match inventory.current_color {
-        None => -1,
-        Some(LightColor::Green) => 0,
-        Some(LightColor::Purple) => 1,
-        Some(LightColor::White) => 2,
-        Some(LightColor::Blue) => 3,
-        // Some(LightColor::Black) => 4,
-    };
+    let color_binds: Vec<(KeyCode, LightColor)> = palette
+        .iter()
+        .map(|(color, def)| (def.key, color))
+        .collect();
+
+    let mut cur_index = inventory
+        .current_color
+        .and_then(|current| color_binds.iter().position(|(_, color)| *color == current))
+        .map_or(-1, |index| index as i32);
 
     for scroll in ev_scroll.read() {
         let sign = -(scroll.y.signum() as i32);
@@ -224,21 +237,23 @@ pub fn handle_color_switch(
         // suspicious algorithm to cycle through available colors with the scroll wheel
         // basically skips disallowed colors until you find the next one
         let mut count = 0;
-        while !inventory.allowed[COLOR_BINDS[new_index.rem_euclid(4) as usize].1]
-            && count < COLOR_BINDS.len()
+        while !inventory.allowed
+            [color_binds[new_index.rem_euclid(color_binds.len() as i32) as usize].1]
+            && count < color_binds.len()
         {
             new_index += sign;
             count += 1;
         }
         cur_index = new_index;
-        if inventory.allowed[COLOR_BINDS[new_index.rem_euclid(4) as usize].1] {
+        if inventory.allowed[color_binds[new_index.rem_euclid(color_binds.len() as i32) as usize].1]
+        {
             beam_actions.write(BeamAction::SwitchColor(Some(
-                COLOR_BINDS[cur_index.rem_euclid(4) as usize].1,
+                color_binds[cur_index.rem_euclid(color_binds.len() as i32) as usize].1,
             )));
         }
     }
 
-    for (key, color) in COLOR_BINDS {
+    for (key, color) in color_binds {
         if keys.just_pressed(key) && inventory.allowed[color] {
             beam_actions.write(BeamAction::SwitchColor(Some(color)));
         }
@@ -295,6 +310,7 @@ pub fn process_beam_actions(
     mut beam_actions: MessageReader<BeamAction>,
     lyra: Single<(&Transform, &mut PlayerLightInventory, &Sprite), With<Lyra>>,
     cursor: Single<&CursorWorldCoords>,
+    palette: Res<LightPalette>,
     // beam_assets: Res<BeamSourceAssets>,
 ) {
     let (player_transform, player_inventory, lyra_sprite) = lyra.into_inner();
@@ -342,13 +358,13 @@ pub fn process_beam_actions(
                     .insert(source_transform)
                     // .with_child((outer_source_sprite, HIGHRES_LAYER))
                     .with_child(LineLight2d::point(
-                        shoot_color.lighting_color().extend(1.0),
+                        palette[shoot_color].lighting_color().extend(1.0),
                         30.0,
                         0.02,
                     ))
                     .id();
 
-                player_inventory.collectible[shoot_color] = Some(LightInventorySource {
+                player_inventory.collectible[shoot_color].push(LightInventorySource {
                     in_reach: false,
                     entity: source,
                 });
@@ -376,18 +392,16 @@ pub fn process_beam_actions(
                 player_inventory.current_color = *color;
             }
             BeamAction::Collect => {
-                for (collect_col, source) in player_inventory.collectible.iter_mut() {
-                    if let Some(s) = source {
-                        if s.in_reach {
-                            commands.entity(s.entity).insert(LightBeamSourceDespawn);
-                            // set source to none once actually despawned
-                            if let Some(pos) = player_inventory
-                                .use_order
-                                .iter()
-                                .position(|col| *col == collect_col)
-                            {
-                                player_inventory.use_order.remove(pos);
-                            }
+                for (collect_col, sources) in player_inventory.collectible.iter() {
+                    for s in sources.iter().filter(|s| s.in_reach) {
+                        commands.entity(s.entity).insert(LightBeamSourceDespawn);
+                        // remove source from the inventory once actually despawned
+                        if let Some(pos) = player_inventory
+                            .use_order
+                            .iter()
+                            .rposition(|col| *col == collect_col)
+                        {
+                            player_inventory.use_order.remove(pos);
                         }
                     }
                 }
@@ -404,8 +418,7 @@ pub fn preview_light_path(
     lyra: Single<(&Transform, &PlayerLightInventory, &Sprite), With<Lyra>>,
     cursor: Single<&CursorWorldCoords>,
     mut gizmos: Gizmos,
-    q_mirror: Query<&Mirror>,
-    // q_black_ray: Query<(Entity, &BlackRayComponent)>,
+    light_interactions: LightInteractionParam,
 ) {
     let (transform, inventory, lyra_sprite) = lyra.into_inner();
     if !inventory.can_shoot() || !inventory.previewing {
@@ -431,19 +444,23 @@ pub fn preview_light_path(
     let dummy_source =
         LightBeamSource::new(ray_pos, ray_dir, shoot_color).with_time_traveled(10000.);
 
-    let playback = play_light_beam(
-        &spatial_query,
-        &dummy_source,
-        // &q_black_ray,
-        &q_mirror,
-    );
-
-    for (a, b) in playback.iter_points(&dummy_source).tuple_windows() {
-        gizmos.line_2d(a, b, shoot_color.light_beam_color().darker(0.3));
+    let playback = play_light_beam(&spatial_query, &dummy_source, &light_interactions);
+
+    for branch in playback.iter_branches() {
+        for (a, b) in branch.iter_points().tuple_windows() {
+            gizmos.line_2d(
+                a,
+                b,
+                light_interactions.palette[branch.color]
+                    .light_beam_color()
+                    .darker(0.3),
+            );
+        }
     }
 }
 
-fn snap_ray(ray_vec: Vec2) -> Vec2 {
+/// Snaps a direction to the closest of the [`NUM_INCREMENTS`] directions beams can be aimed in.
+pub fn snap_ray(ray_vec: Vec2) -> Vec2 {
     let ray_angle = (ray_vec.y.atan2(ray_vec.x) + (2.0 * PI)) % (2.0 * PI);
     let increment_angle = (2.0 * PI) / NUM_INCREMENTS as f32;
     let snapped_angle = (ray_angle / increment_angle).round() * increment_angle;
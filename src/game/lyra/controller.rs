@@ -3,7 +3,8 @@ use bevy::{ecs::query::Has, prelude::*};
 
 use crate::{
     game::{
-        lyra::{Lyra, LyraWallCaster},
+        defs::moving_platform::MovingPlatform,
+        lyra::{zipline::Zipline, Lyra, LyraWallCaster},
         LevelSystems,
     },
     shared::PlayState,
@@ -70,6 +71,8 @@ pub enum MovementAction {
     JumpCut,
     Crouch,
     Stand,
+    /// Grab onto a nearby beam and ride it like a zipline
+    Grab,
 }
 
 /// A marker component indicating that an entity is using a character controller.
@@ -88,6 +91,8 @@ pub struct MovementInfo {
     pub coyote_time_ticks: isize,
     pub jump_boost_ticks: isize,
     pub crouched: bool,
+    /// Velocity inherited from the [`MovingPlatform`] the player stood on last step
+    pub platform_velocity: Vector,
 }
 
 pub fn keyboard_input(
@@ -110,6 +115,9 @@ pub fn keyboard_input(
     if keyboard_input.just_released(KeyCode::Space) {
         movement_writer.write(MovementAction::JumpCut);
     }
+    if keyboard_input.any_just_pressed([KeyCode::KeyW, KeyCode::ArrowUp]) {
+        movement_writer.write(MovementAction::Grab);
+    }
     if keyboard_input.just_pressed(KeyCode::ControlLeft) {
         movement_writer.write(MovementAction::Crouch);
     }
@@ -133,19 +141,28 @@ pub fn update_grounded(
 }
 
 pub fn movement(
+    mut commands: Commands,
     mut movement_reader: MessageReader<MovementAction>,
     lyra: Single<
         (
+            Entity,
             &mut MovementInfo,
             &mut LinearVelocity,
             &ShapeHits,
             Has<Grounded>,
+            Has<Zipline>,
         ),
         With<Lyra>,
     >,
     wall_casters: Query<(&ShapeHits, &LyraWallCaster), Without<Lyra>>,
+    q_platforms: Query<&LinearVelocity, (With<MovingPlatform>, Without<Lyra>)>,
 ) {
-    let (mut movement_info, mut linear_velocity, shape_hits, is_grounded) = lyra.into_inner();
+    let (entity, mut movement_info, mut linear_velocity, shape_hits, is_grounded, mut riding) =
+        lyra.into_inner();
+
+    // work with the player's own velocity, the platform's velocity is added back at the end
+    linear_velocity.0 -= movement_info.platform_velocity;
+
     if is_grounded {
         movement_info.coyote_time_ticks = COYOTE_TIME_TICKS;
     }
@@ -153,11 +170,18 @@ pub fn movement(
     let mut moved = false;
     for event in movement_reader.read() {
         match event {
+            // the zipline moves lyra instead
+            MovementAction::Move(_) if riding => {}
             MovementAction::Move(direction) => {
                 linear_velocity.x += *direction * PLAYER_MOVE_VEL * 64.;
                 moved = true;
             }
             MovementAction::Jump => {
+                if riding {
+                    commands.entity(entity).remove::<Zipline>();
+                    movement_info.coyote_time_ticks = COYOTE_TIME_TICKS;
+                    riding = false;
+                }
                 movement_info.should_jump_ticks = SHOULD_JUMP_TICKS;
             }
             MovementAction::JumpCut => {
@@ -169,9 +193,14 @@ pub fn movement(
             }
             MovementAction::Crouch => movement_info.crouched = true,
             MovementAction::Stand => movement_info.crouched = false,
+            MovementAction::Grab => {}
         }
     }
 
+    if riding {
+        return;
+    }
+
     if movement_info.should_jump_ticks > 0 && movement_info.coyote_time_ticks > 0 {
         movement_info.jump_boost_ticks = JUMP_BOOST_TICKS;
     }
@@ -225,6 +254,14 @@ pub fn movement(
         PLAYER_MAX_H_VEL * 64. * crouch_modif,
     );
 
+    let platform_velocity = shape_hits
+        .iter()
+        .find_map(|hit| q_platforms.get(hit.entity).ok())
+        .map(|velocity| velocity.0)
+        .unwrap_or(Vector::ZERO);
+    linear_velocity.0 += platform_velocity;
+    movement_info.platform_velocity = platform_velocity;
+
     movement_info.should_jump_ticks -= 1;
     movement_info.jump_boost_ticks -= 1;
     movement_info.coyote_time_ticks -= 1;
@@ -14,7 +14,7 @@ use crate::ldtk::LdtkParam;
 use crate::{
     camera::HIGHRES_LAYER,
     game::{
-        light::LightColor,
+        light::{palette::LightPalette, LightColor},
         lyra::{beam::PlayerLightInventory, Lyra},
         LevelSystems,
     },
@@ -89,7 +89,7 @@ pub fn lerp_translations(
 #[reflect(Resource)]
 pub struct IndicatorAssets {
     #[dependency]
-    icons: [Handle<Image>; 4],
+    icons: [Handle<Image>; 5],
 }
 
 impl FromWorld for IndicatorAssets {
@@ -102,6 +102,7 @@ impl FromWorld for IndicatorAssets {
                 asset_server.load("indicator-shard/purple-shard.png"),
                 asset_server.load("indicator-shard/white-shard.png"),
                 asset_server.load("indicator-shard/blue-shard.png"),
+                asset_server.load("indicator-shard/black-shard.png"),
             ],
         }
     }
@@ -122,21 +123,18 @@ pub fn init_light_indicators(
     mut indicators: ResMut<LightIndicators>,
     ldtk_param: LdtkParam,
     lyra: Single<(&Transform, &PlayerLightInventory), With<Lyra>>,
+    palette: Res<LightPalette>,
 ) {
     let (transform, inventory) = lyra.into_inner();
-    let color_ind = |color: LightColor| match color {
-        LightColor::Green => 0,
-        LightColor::Purple => 1,
-        LightColor::White => 2,
-        LightColor::Blue => 3,
-    };
     for (color, entity) in indicators.indicators.iter_mut() {
         let id = commands
-            .spawn(Sprite::from_image(assets.icons[color_ind(color)].clone()))
+            .spawn(Sprite::from_image(
+                assets.icons[palette[color].indicator_icon].clone(),
+            ))
             .insert(LightShardIndicator)
             .insert(HIGHRES_LAYER)
             .with_child((
-                LineLight2d::point(color.lighting_color().extend(1.0), 20.0, 0.04),
+                LineLight2d::point(palette[color].lighting_color().extend(1.0), 20.0, 0.04),
                 TERRAIN_LAYER,
             ))
             .id();
@@ -270,8 +268,9 @@ pub fn update_light_indicator(
                         base_transform.translation + indicator_transform.translation,
                     ));
             }
-            let Some((source_transform, _)) =
-                q_beam_sources.iter().find(|(_, source)| source.color == c)
+            let Some((source_transform, _)) = inventory.collectible[c]
+                .last()
+                .and_then(|source| q_beam_sources.get(source.entity).ok())
             else {
                 return true;
             };
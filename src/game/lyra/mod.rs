@@ -10,6 +10,7 @@ use crate::{
         camera_op::SnapToLyra,
         defs::{
             one_way_platform::PassThroughOneWayPlatform,
+            rotatable_mirror::{on_player_enter_mirror_reach, on_player_leave_mirror_reach},
             shard::on_player_intersect_shard,
             tooltip_sign::{display_tooltip_signs, hide_tooltip_signs},
         },
@@ -28,6 +29,7 @@ use crate::{
             kill::{kill_player_on_danger, LyraKillPlugin},
             restart_hint::HintRestartPlugin,
             strand::LyraStrandPlugin,
+            zipline::LyraZiplinePlugin,
         },
         Layers,
     },
@@ -41,7 +43,8 @@ pub mod controller;
 pub mod indicator;
 mod kill;
 mod restart_hint;
-mod strand;
+pub mod strand;
+mod zipline;
 
 pub const LYRA_RESPAWN_EPSILON: f32 = 3.0;
 
@@ -58,6 +61,7 @@ impl Plugin for LyraPlugin {
         app.add_plugins(BeamControllerPlugin);
         app.add_plugins(HintRestartPlugin);
         app.add_plugins(LightIndicatorPlugin);
+        app.add_plugins(LyraZiplinePlugin);
         app.add_systems(OnEnter(GameState::InGame), spawn_lyra);
         app.add_systems(OnEnter(GameState::InGame), spawn_lyra_cam.after(spawn_lyra));
         app.add_systems(OnExit(GameState::InGame), despawn_lyra);
@@ -98,6 +102,10 @@ pub fn spawn_lyra(
     info!("Spawning Lyra!");
 
     let lyra_transform = lyra_spawn_transform(&ldtk_level_param);
+    let mut light_inventory = PlayerLightInventory::from(player_light_save_data.into_inner());
+    if let Some(level) = ldtk_level_param.cur_level() {
+        light_inventory.charges = level.raw().light_charges();
+    }
     // NOTE: actual z value doesn't matter because lyra is rendered on a separate layer
     let player = commands
         .spawn(Lyra)
@@ -129,7 +137,13 @@ pub fn spawn_lyra(
         .entity(player)
         .insert(CollisionLayers::new(
             Layers::PlayerCollider,
-            [Layers::Terrain, Layers::BlueCrystal, Layers::Platform],
+            [
+                Layers::Terrain,
+                Layers::BlueCrystal,
+                Layers::Platform,
+                Layers::LightBridge,
+                Layers::PhaseBlock,
+            ],
         ))
         .insert(CharacterController)
         .insert(RigidBody::Dynamic)
@@ -153,6 +167,8 @@ pub fn spawn_lyra(
                 Layers::Terrain,
                 Layers::BlueCrystal,
                 Layers::Platform,
+                Layers::LightBridge,
+                Layers::PhaseBlock,
             ])),
         )
         .insert(LockedAxes::ROTATION_LOCKED)
@@ -166,9 +182,7 @@ pub fn spawn_lyra(
             combine_rule: CoefficientCombine::Min,
         })
         .insert(CachedLinearVelocity(Vec2::ZERO))
-        .insert(PlayerLightInventory::from(
-            player_light_save_data.into_inner(),
-        ))
+        .insert(light_inventory)
         .insert(PlayerAnimationType::Idle)
         .insert(PassThroughOneWayPlatform::ByNormal)
         .insert(AnimationConfig::from(PlayerAnimationType::Idle));
@@ -200,7 +214,9 @@ pub fn spawn_lyra(
         .observe(display_tooltip_signs)
         .observe(handle_start_end_markers)
         .observe(on_player_intersect_shard)
-        .observe(kill_player_on_danger);
+        .observe(kill_player_on_danger)
+        .observe(on_player_enter_mirror_reach)
+        .observe(on_player_leave_mirror_reach);
 
     commands
         .spawn(
@@ -212,9 +228,11 @@ pub fn spawn_lyra(
             )
             .with_max_distance(0.5)
             .with_max_hits(10)
-            .with_query_filter(
-                SpatialQueryFilter::default().with_mask([Layers::Terrain, Layers::BlueCrystal]),
-            ),
+            .with_query_filter(SpatialQueryFilter::default().with_mask([
+                Layers::Terrain,
+                Layers::BlueCrystal,
+                Layers::PhaseBlock,
+            ])),
         )
         .insert(LyraWallCaster::Right)
         .insert(ChildOf(player));
@@ -229,9 +247,11 @@ pub fn spawn_lyra(
             )
             .with_max_distance(0.5)
             .with_max_hits(10)
-            .with_query_filter(
-                SpatialQueryFilter::default().with_mask([Layers::Terrain, Layers::BlueCrystal]),
-            ),
+            .with_query_filter(SpatialQueryFilter::default().with_mask([
+                Layers::Terrain,
+                Layers::BlueCrystal,
+                Layers::PhaseBlock,
+            ])),
         )
         .insert(LyraWallCaster::Left)
         .insert(ChildOf(player));
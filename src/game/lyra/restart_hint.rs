@@ -52,7 +52,7 @@ pub fn hint_restart_button(
     let can_shoot = inventory
         .collectible
         .iter()
-        .any(|(c, avail)| avail.is_none() && inventory.allowed[c]);
+        .any(|(c, _)| inventory.can_shoot_color(c));
 
     if !can_shoot && has_color {
         triggered.tick(time.delta());
@@ -65,7 +65,7 @@ pub struct Strand {
 }
 
 impl Strand {
-    fn new(
+    pub fn new(
         connect: Entity,
         offset: Vec2,
         dist: f32,
@@ -308,6 +308,8 @@ pub fn add_player_strand(
                 Layers::Terrain,
                 Layers::BlueCrystal,
                 Layers::Platform,
+                Layers::LightBridge,
+                Layers::PhaseBlock,
             ]);
             commands.entity(new_id).insert(
                 RayCaster::new(Vec2::ZERO, Dir2::NEG_Y)
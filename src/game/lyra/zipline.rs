@@ -0,0 +1,239 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    game::{
+        light::{
+            segments::{LightBeamSourceDespawn, LightSegment},
+            LightBeamSource,
+        },
+        lyra::{
+            beam::PlayerLightInventory,
+            controller::{movement, MovementAction, MovementInfo},
+            Lyra,
+        },
+        LevelSystems,
+    },
+    shared::ResetLevels,
+};
+
+/// How far below the beam Lyra's center hangs while she rides it.
+const ZIPLINE_HANG_OFFSET: f32 = 8.;
+/// How far Lyra's hands can be from a beam to grab onto it.
+const ZIPLINE_GRAB_DISTANCE: f32 = 6.;
+/// How fast Lyra slides along a beam.
+const ZIPLINE_SPEED: f32 = 2.5;
+/// The number of [`FixedUpdate`] steps Lyra can be stuck on something before she lets go.
+const ZIPLINE_STUCK_TICKS: usize = 4;
+
+pub struct LyraZiplinePlugin;
+
+impl Plugin for LyraZiplinePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (grab_beam.before(movement), ride_zipline.after(movement))
+                .in_set(LevelSystems::Simulation),
+        );
+        app.add_observer(release_zipline_on_reset);
+    }
+}
+
+/// [`Component`] on Lyra while she slides along one of her beams. She follows the branch of the
+/// beam she grabbed through each of its bounces until she reaches its end, and lets go when she
+/// jumps, gets stuck, or the beam moves away from her.
+#[derive(Component, Debug)]
+#[component(storage = "SparseSet")]
+pub struct Zipline {
+    source: Entity,
+    branch: usize,
+    /// Whether Lyra travels in the direction the beam was cast
+    forward: bool,
+    /// The point of the beam Lyra is heading to
+    target: Vec2,
+    last_distance: f32,
+    stuck_ticks: usize,
+}
+
+impl Zipline {
+    /// Where Lyra's center is when she hangs from the beam at `point`.
+    fn hang_pos(point: Vec2) -> Vec2 {
+        point - Vec2::new(0., ZIPLINE_HANG_OFFSET)
+    }
+
+    /// Where Lyra's hands are when her center is at `pos`.
+    fn hands_pos(pos: Vec2) -> Vec2 {
+        pos + Vec2::new(0., ZIPLINE_HANG_OFFSET)
+    }
+}
+
+/// The points of each branch of a beam, in the order they were cast, from the [`Transform`]s of its
+/// segments.
+fn beam_paths<'a>(
+    segments: impl Iterator<Item = (&'a LightSegment, &'a Transform)>,
+) -> Vec<(&'a LightSegment, Vec<Vec2>)> {
+    let mut segments: Vec<_> = segments.collect();
+    segments.sort_by_key(|(segment, _)| (segment.source, segment.branch, segment.index));
+
+    let mut paths: Vec<(&LightSegment, Vec<Vec2>)> = Vec::new();
+    for (segment, transform) in segments {
+        let half = (transform.rotation * Vec3::X).truncate() * transform.scale.x / 2.;
+        let center = transform.translation.truncate();
+        match paths.last_mut() {
+            Some((first, points))
+                if first.source == segment.source && first.branch == segment.branch =>
+            {
+                points.push(center + half);
+            }
+            _ => paths.push((segment, vec![center - half, center + half])),
+        }
+    }
+    paths
+}
+
+/// The index of the segment of `points` closest to `pos`, and how far away it is.
+fn closest_segment(points: &[Vec2], pos: Vec2) -> Option<(usize, f32)> {
+    points
+        .windows(2)
+        .map(|pair| {
+            let (a, b) = (pair[0], pair[1]);
+            let t = ((pos - a).dot(b - a) / (b - a).length_squared()).clamp(0., 1.);
+            pos.distance(a + (b - a) * t)
+        })
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// [`System`] that lets Lyra grab onto one of her beams when [`MovementAction::Grab`] is pressed
+/// near it. She rides it in the direction she is facing.
+#[allow(clippy::type_complexity)]
+pub fn grab_beam(
+    mut commands: Commands,
+    mut movement_reader: MessageReader<MovementAction>,
+    lyra: Single<
+        (
+            Entity,
+            &Transform,
+            &Sprite,
+            &PlayerLightInventory,
+            &mut MovementInfo,
+        ),
+        (With<Lyra>, Without<Zipline>),
+    >,
+    q_segments: Query<(&LightSegment, &Transform)>,
+) {
+    if !movement_reader
+        .read()
+        .any(|action| matches!(action, MovementAction::Grab))
+    {
+        return;
+    }
+    let (entity, transform, sprite, inventory, mut movement_info) = lyra.into_inner();
+    let hands = Zipline::hands_pos(transform.translation.truncate());
+    let facing = if sprite.flip_x { -1. } else { 1. };
+
+    let own_segments = q_segments.iter().filter(|(segment, _)| {
+        inventory.collectible[segment.color]
+            .iter()
+            .any(|source| source.entity == segment.source)
+    });
+
+    let closest = beam_paths(own_segments)
+        .into_iter()
+        .filter_map(|(segment, points)| {
+            let (i, distance) = closest_segment(&points, hands)?;
+            Some((segment, points[i], points[i + 1], distance))
+        })
+        .filter(|(_, _, _, distance)| *distance <= ZIPLINE_GRAB_DISTANCE)
+        .min_by(|a, b| a.3.total_cmp(&b.3));
+
+    let Some((segment, start, end, _)) = closest else {
+        return;
+    };
+    let forward = (end.x - start.x) * facing >= 0.;
+
+    movement_info.platform_velocity = Vec2::ZERO;
+    commands.entity(entity).insert(Zipline {
+        source: segment.source,
+        branch: segment.branch,
+        forward,
+        target: if forward { end } else { start },
+        last_distance: f32::INFINITY,
+        stuck_ticks: 0,
+    });
+}
+
+/// [`System`] that slides Lyra along the beam she is riding, and lets go of it once she reaches
+/// the end, gets stuck, or the beam is gone. The path is rebuilt from the beam's segments every
+/// tick, so that she follows the beam as it changes.
+pub fn ride_zipline(
+    mut commands: Commands,
+    lyra: Single<(Entity, &Transform, &mut LinearVelocity, &mut Zipline), With<Lyra>>,
+    q_sources: Query<Has<LightBeamSourceDespawn>, With<LightBeamSource>>,
+    q_segments: Query<(&LightSegment, &Transform)>,
+    time: Res<Time>,
+) {
+    let (entity, transform, mut linear_velocity, mut zipline) = lyra.into_inner();
+    let pos = transform.translation.truncate();
+    let step = ZIPLINE_SPEED * 64. * time.delta_secs();
+
+    let target = match q_sources.get(zipline.source) {
+        Ok(false) => {
+            let own_segments = q_segments.iter().filter(|(segment, _)| {
+                segment.source == zipline.source && segment.branch == zipline.branch
+            });
+            beam_paths(own_segments)
+                .pop()
+                .and_then(|(_, points)| next_target(&points, pos, zipline.forward, step))
+        }
+        // the beam is gone, or being pulled back
+        _ => None,
+    };
+    let Some(target) = target else {
+        commands.entity(entity).remove::<Zipline>();
+        return;
+    };
+
+    if target.distance(zipline.target) > step {
+        zipline.target = target;
+        zipline.last_distance = f32::INFINITY;
+    }
+    let distance = pos.distance(Zipline::hang_pos(target));
+    if distance >= zipline.last_distance - step / 4. {
+        zipline.stuck_ticks += 1;
+    } else {
+        zipline.stuck_ticks = 0;
+    }
+    zipline.last_distance = distance;
+    if zipline.stuck_ticks > ZIPLINE_STUCK_TICKS {
+        commands.entity(entity).remove::<Zipline>();
+        return;
+    }
+
+    linear_velocity.0 = (Zipline::hang_pos(target) - pos).normalize_or_zero() * ZIPLINE_SPEED * 64.;
+}
+
+/// The point of the beam `points` Lyra heads to from `pos`, skipping the points she is within
+/// `step` of. Returns [`None`] once she reaches the end, or if she is too far from the beam.
+fn next_target(points: &[Vec2], pos: Vec2, forward: bool, step: f32) -> Option<Vec2> {
+    let (i, distance) = closest_segment(points, Zipline::hands_pos(pos))?;
+    if distance > ZIPLINE_GRAB_DISTANCE {
+        return None;
+    }
+    let remaining: Vec<Vec2> = if forward {
+        points[i + 1..].to_vec()
+    } else {
+        points[..=i].iter().rev().copied().collect()
+    };
+    remaining
+        .into_iter()
+        .find(|point| pos.distance(Zipline::hang_pos(*point)) > step)
+}
+
+pub fn release_zipline_on_reset(
+    _: On<ResetLevels>,
+    mut commands: Commands,
+    lyra: Single<Entity, With<Lyra>>,
+) {
+    commands.entity(*lyra).remove::<Zipline>();
+}
@@ -1,6 +1,7 @@
 use avian2d::prelude::*;
 use bevy::prelude::*;
 use bevy_ecs_ldtk::systems::process_ldtk_levels;
+use serde::Deserialize;
 
 use crate::{
     camera::HIGHRES_LAYER,
@@ -105,7 +106,7 @@ impl Plugin for GamePlugin {
     }
 }
 
-#[derive(PhysicsLayer, Default)]
+#[derive(PhysicsLayer, Default, Deserialize)]
 pub enum Layers {
     #[default]
     Default,
@@ -125,7 +126,14 @@ pub enum Layers {
     // non collision based, just sensors
     LightSensor,
     SensorBox,
-    // BlackRay,
+    // beams refract through water, but it isn't solid
+    Water,
+    Portal,
+    BlackRay,
+    // beams and vines that Lyra can stand on, which beams pass through
+    LightBridge,
+    // the part of phase blocks that Lyra collides with, which beams pass through
+    PhaseBlock,
 }
 
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
@@ -136,6 +136,34 @@ impl DustSurface {
     }
 }
 
+/// [`Message`] that scatters wall debris over `rect`, e.g. when a cracked wall breaks.
+#[derive(Message)]
+pub struct DebrisExplosionEvent {
+    pub rect: Rect,
+}
+
+pub fn create_debris_explosions(
+    mut commands: Commands,
+    mut debris_explosion_events: MessageReader<DebrisExplosionEvent>,
+    dust_assets: Res<DustAssets>,
+) {
+    for event in debris_explosion_events.read() {
+        // about one piece of debris per 4x4 pixels of wall
+        let amount = (event.rect.size().x * event.rect.size().y / 16.) as usize;
+        for _ in 0..amount.clamp(8, 64) {
+            let pos = Vec2::new(
+                rand::random_range(event.rect.min.x..=event.rect.max.x),
+                rand::random_range(event.rect.min.y..=event.rect.max.y),
+            );
+            let starting_velocity = DustSurface::Wall.new_starting_velocity() * 1.5;
+            commands.spawn(ParticleBundle::new(
+                DustSurface::Wall.new_particle_options(starting_velocity, &dust_assets),
+                pos,
+            ));
+        }
+    }
+}
+
 #[derive(Resource, Default)]
 pub struct DustSpawnStopwatch {
     pub walking: Stopwatch,
@@ -1,7 +1,10 @@
 use std::time::Duration;
 
 use bevy::prelude::*;
-use dust::{add_crystal_dust, spawn_player_walking_dust, DustSpawnStopwatch};
+use dust::{
+    add_crystal_dust, create_debris_explosions, spawn_player_walking_dust, DebrisExplosionEvent,
+    DustSpawnStopwatch,
+};
 use emitter::{
     update_particle_emitters, ParticleEmitter, ParticleEmitterArea, ParticleEmitterOptions,
 };
@@ -35,6 +38,7 @@ impl Plugin for ParticlePlugin {
         app.insert_resource(Wind::new());
         app.insert_resource(DustSpawnStopwatch::default());
         app.add_message::<SparkExplosionEvent>();
+        app.add_message::<DebrisExplosionEvent>();
         app.add_observer(add_segment_sparks);
         app.add_systems(
             Update,
@@ -50,6 +54,7 @@ impl Plugin for ParticlePlugin {
                 spawn_player_walking_dust,
                 add_crystal_dust,
                 create_spark_explosions,
+                create_debris_explosions,
             )
                 .in_set(LevelSystems::Simulation),
         );
@@ -3,7 +3,7 @@ use std::time::Duration;
 use bevy::prelude::*;
 
 use crate::game::{
-    light::segments::LightSegment,
+    light::{palette::LightPalette, segments::LightSegment},
     particle::{
         emitter::ParticleModifier, ParticleBundle, ParticleEmitter, ParticleEmitterArea,
         ParticleEmitterOptions, ParticleOptions, ParticlePhysicsOptions,
@@ -32,6 +32,7 @@ pub fn add_segment_sparks(
     mut commands: Commands,
     light_segment: Query<&LightSegment>,
     spark_assets: Res<SparkAssets>,
+    palette: Res<LightPalette>,
 ) {
     const VEL: f32 = 30.0;
 
@@ -46,7 +47,7 @@ pub fn add_segment_sparks(
             delay_range: Duration::from_secs_f32(0.0)..Duration::from_secs_f32(500.0),
             scale_delay_by_area: true,
             particles: vec![new_spark_particle(
-                segment.color.light_beam_color(),
+                palette[segment.color].light_beam_color(),
                 &spark_assets,
             )],
             modifier: ParticleModifier {
@@ -58,7 +59,7 @@ pub fn add_segment_sparks(
                 area: ParticleEmitterArea::Circle { radius: 0.5 },
                 delay_range: Duration::from_secs_f32(0.0)..Duration::from_secs_f32(0.4),
                 particles: vec![new_spark_particle(
-                    segment.color.light_beam_color(),
+                    palette[segment.color].light_beam_color(),
                     &spark_assets,
                 )],
                 modifier: ParticleModifier {
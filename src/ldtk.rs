@@ -5,6 +5,7 @@ use bevy_ecs_ldtk::{
     prelude::LdtkFields,
     LevelIid, LevelSelection,
 };
+use enum_map::{enum_map, EnumMap};
 
 use crate::game::{light::LightColor, setup::LevelAssets};
 
@@ -13,6 +14,10 @@ pub trait LevelExt {
     fn start_flag_pos(&self) -> Option<Vec2>;
     fn level_box(&self) -> Rect;
     fn level_id(&self) -> &String;
+    fn light_charges(&self) -> EnumMap<LightColor, usize>;
+    fn beam_range(&self) -> Option<f32>;
+    fn light_bridges(&self) -> EnumMap<LightColor, bool>;
+    fn wall_break_time(&self) -> Option<f32>;
 }
 
 impl LevelExt for Level {
@@ -48,6 +53,51 @@ impl LevelExt for Level {
         level_id
     }
 
+    /// The number of beams of each color Lyra can have out at once in this level. Each entry in the
+    /// `LightCharges` level field is one charge of that color, and colors that aren't listed get
+    /// a single charge.
+    fn light_charges(&self) -> EnumMap<LightColor, usize> {
+        let mut listed: EnumMap<LightColor, usize> = enum_map! { _ => 0 };
+        if let Ok(colors) = self.get_maybe_enums_field("LightCharges") {
+            for color in colors.iter().flatten() {
+                listed[LightColor::from(color)] += 1;
+            }
+        }
+        listed.map(|_, count| count.max(1))
+    }
+
+    /// How far beams travel in this level, as a multiple of their color's range. Levels without
+    /// the `BeamRange` level field don't limit how far beams go.
+    fn beam_range(&self) -> Option<f32> {
+        self.get_maybe_float_field("BeamRange")
+            .ok()
+            .copied()
+            .flatten()
+            .filter(|scale| *scale > 0.)
+    }
+
+    /// The colors listed in the `LightBridges` level field, whose beams Lyra can stand on in this
+    /// level.
+    fn light_bridges(&self) -> EnumMap<LightColor, bool> {
+        let mut bridges: EnumMap<LightColor, bool> = enum_map! { _ => false };
+        if let Ok(colors) = self.get_maybe_enums_field("LightBridges") {
+            for color in colors.iter().flatten() {
+                bridges[LightColor::from(color)] = true;
+            }
+        }
+        bridges
+    }
+
+    /// How many seconds beams need to hit a cracked wall for it to break, from the optional
+    /// `WallBreakTime` level field.
+    fn wall_break_time(&self) -> Option<f32> {
+        self.get_maybe_float_field("WallBreakTime")
+            .ok()
+            .copied()
+            .flatten()
+            .filter(|secs| *secs > 0.)
+    }
+
     fn level_box(&self) -> Rect {
         Rect::new(
             self.world_x as f32,
@@ -146,3 +196,19 @@ impl LdtkLevelParam<'_> {
             .map(|level| LevelIid::new(level.raw().iid.as_str()))
     }
 }
+
+/// [`SystemParam`] that finds the level an entity was spawned in, from the [`LevelIid`] of its
+/// closest ancestor.
+#[derive(SystemParam)]
+pub struct EntityLevelParam<'w, 's> {
+    q_parents: Query<'w, 's, &'static ChildOf>,
+    q_levels: Query<'w, 's, &'static LevelIid>,
+}
+
+impl EntityLevelParam<'_, '_> {
+    pub fn level_of(&self, entity: Entity) -> Option<&LevelIid> {
+        std::iter::once(entity)
+            .chain(self.q_parents.iter_ancestors(entity))
+            .find_map(|ancestor| self.q_levels.get(ancestor).ok())
+    }
+}
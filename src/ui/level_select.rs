@@ -11,7 +11,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::asset::LoadResource;
 use crate::config::Config;
-use crate::game::light::LightColor;
+use crate::game::defs::sensor::SensorRequirement;
+use crate::game::light::{palette::LightPalette, LightColor};
 use crate::ldtk::LdtkParam;
 use crate::save::SaveParam;
 use crate::shared::{GameState, UiState};
@@ -42,7 +43,7 @@ const SENSOR_ENTITY_IDENT: &str = "Sensor";
 const SENSOR_COLOR_IDENT: &str = "toggle_color";
 
 // [R, G, B, A] colors for level preview
-const LEVEL_PREVIEW_COLORS: [[u8; 4]; 17] = [
+const LEVEL_PREVIEW_COLORS: [[u8; 4]; 42] = [
     [0, 0, 0, 255],       // intgrid 0
     [41, 54, 78, 255],    // intgrid 1
     [117, 158, 202, 255], // intgrid 2
@@ -60,6 +61,31 @@ const LEVEL_PREVIEW_COLORS: [[u8; 4]; 17] = [
     [0, 0, 0, 255],       // intgrid 14
     [115, 62, 57, 255],   // intgrid 15
     [200, 200, 200, 255], // intgrid 16
+    [150, 220, 170, 255], // intgrid 17
+    [200, 170, 235, 255], // intgrid 18
+    [235, 235, 240, 255], // intgrid 19
+    [170, 215, 245, 255], // intgrid 20
+    [40, 150, 70, 255],   // intgrid 21
+    [120, 50, 190, 255],  // intgrid 22
+    [160, 160, 170, 255], // intgrid 23
+    [50, 130, 210, 255],  // intgrid 24
+    [190, 230, 230, 255], // intgrid 25
+    [30, 90, 160, 255],   // intgrid 26
+    [33, 44, 63, 255],    // intgrid 27
+    [33, 44, 63, 255],    // intgrid 28
+    [33, 44, 63, 255],    // intgrid 29
+    [33, 44, 63, 255],    // intgrid 30
+    [150, 150, 150, 255], // intgrid 31
+    [150, 150, 150, 255], // intgrid 32
+    [150, 150, 150, 255], // intgrid 33
+    [150, 150, 150, 255], // intgrid 34
+    [230, 215, 150, 255], // intgrid 35
+    [100, 75, 150, 255],  // intgrid 36
+    [90, 77, 77, 255],    // intgrid 37
+    [70, 110, 80, 255],   // intgrid 38
+    [100, 75, 120, 255],  // intgrid 39
+    [125, 120, 120, 255], // intgrid 40
+    [75, 100, 125, 255],  // intgrid 41
 ];
 
 fn sensor_color_to_rgba(sensor_color: &str) -> [u8; 4] {
@@ -384,6 +410,7 @@ fn ensure_level_preview_image(
     level: &bevy_ecs_ldtk::ldtk::Level,
     level_preview_store: &mut LevelPreviewStore,
     assets: &mut Assets<Image>,
+    palette: &LightPalette,
 ) -> (Vec2, Handle<Image>) {
     let level_id = level
         .get_string_field("LevelId")
@@ -449,7 +476,11 @@ fn ensure_level_preview_image(
                 }
             })
             .expect("Could not find sensor color field!");
-        let rgba = sensor_color_to_rgba(entity_color);
+        // sensors that only some colors charge are drawn in those colors instead
+        let rgba = match SensorRequirement::from(entity).indicator_color(palette) {
+            Some(color) => color.to_srgba().to_u8_array(),
+            None => sensor_color_to_rgba(entity_color),
+        };
         let idx = (entity_coords.y as usize * layer_w + entity_coords.x as usize) * pixel_size;
         level_preview_data[idx..idx + pixel_size].copy_from_slice(&rgba[..pixel_size]);
     }
@@ -538,6 +569,7 @@ pub fn handle_level_selection(
     >,
     mut commands: Commands,
     level_progress: Res<LevelProgress>,
+    palette: Res<LightPalette>,
 ) {
     let Some(project) = ldtk_param.project() else {
         return;
@@ -561,8 +593,12 @@ pub fn handle_level_selection(
                 break;
             }
             Interaction::Hovered => {
-                let (level_dims, level_preview_img) =
-                    ensure_level_preview_image(level, &mut level_preview_store, &mut assets);
+                let (level_dims, level_preview_img) = ensure_level_preview_image(
+                    level,
+                    &mut level_preview_store,
+                    &mut assets,
+                    &palette,
+                );
                 let (level_preview_entity, ref mut level_preview_nodes) = *level_preview;
                 let locked = level_progress.0[index.1].locked;
 
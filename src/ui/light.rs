@@ -4,7 +4,7 @@ use enum_map::{enum_map, EnumMap};
 use crate::{
     asset::LoadResource,
     game::{
-        light::LightColor,
+        light::{palette::LightPalette, LightColor},
         lyra::{beam::PlayerLightInventory, Lyra},
     },
     shared::GameState,
@@ -35,7 +35,7 @@ pub struct LightUiIcons {
 #[reflect(Resource)]
 pub struct LightUiAssets {
     #[dependency]
-    icons: [Handle<Image>; 4],
+    icons: [Handle<Image>; 5],
 }
 
 impl FromWorld for LightUiAssets {
@@ -48,6 +48,7 @@ impl FromWorld for LightUiAssets {
                 asset_server.load("ui/purple_light_icon.png"),
                 asset_server.load("ui/blue_light_icon.png"),
                 asset_server.load("ui/white_light_icon.png"),
+                asset_server.load("ui/black_light_icon.png"),
             ],
         }
     }
@@ -57,12 +58,14 @@ pub fn spawn_light_icons(
     mut commands: Commands,
     light_ui_assets: Res<LightUiAssets>,
     ui_font: Res<UiFont>,
+    palette: Res<LightPalette>,
 ) {
     let icons: EnumMap<LightColor, Handle<Image>> = enum_map! {
         LightColor::Green => light_ui_assets.icons[0].clone(),
         LightColor::Purple => light_ui_assets.icons[1].clone(),
         LightColor::Blue => light_ui_assets.icons[2].clone(),
         LightColor::White => light_ui_assets.icons[3].clone(),
+        LightColor::Black => light_ui_assets.icons[4].clone(),
     };
 
     let overlay = commands
@@ -92,12 +95,7 @@ pub fn spawn_light_icons(
         .id();
 
     let mut spawn_and_get_icon_id = |val: LightColor| {
-        let text = match val {
-            LightColor::Green => "1",
-            LightColor::Purple => "2",
-            LightColor::White => "3",
-            LightColor::Blue => "4",
-        };
+        let text = key_label(palette[val].key);
         let icon = commands
             .spawn(ImageNode::from(icons[val].clone()))
             .insert(Node {
@@ -137,6 +135,17 @@ pub fn spawn_light_icons(
     commands.insert_resource(LightUiIcons { icon_entities });
 }
 
+/// The text shown next to a light icon for the `key` that switches to its color, e.g. `1` for
+/// [`KeyCode::Digit1`].
+fn key_label(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+    ["Digit", "Numpad", "Key"]
+        .iter()
+        .find_map(|prefix| name.strip_prefix(*prefix))
+        .unwrap_or(name.as_str())
+        .to_string()
+}
+
 #[derive(Component)]
 pub struct LightUiMarker;
 
@@ -154,7 +163,7 @@ pub fn update_light_icons(
         let is_color = inventory.current_color.is_some_and(|c| color == c);
         if *can_use {
             icon_node.display = Display::Block;
-            if inventory.collectible[color].is_none() {
+            if inventory.can_shoot_color(color) {
                 if is_color {
                     icon_image.color.set_alpha(1.0);
                 } else {